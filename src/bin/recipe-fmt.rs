@@ -0,0 +1,95 @@
+//! Checks recipe files for parse errors and likely mistakes, and optionally
+//! rewrites them in the canonical `.txt` format. Files with comments are left
+//! as they are, since the canonical format has no comments.
+//!
+//! ```text
+//! recipe-fmt [--write] <recipe.txt>...
+//! ```
+//!
+//! Exits with 0 when every file is fine, 1 when any file fails to parse and 2
+//! when any file has warnings.
+
+use std::process::ExitCode;
+
+use recipe_book::{recipe_util::is_comment, Recipe};
+
+/// How a file fared, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Outcome {
+    Ok,
+    Warnings,
+    ParseError,
+}
+
+impl Outcome {
+    fn exit_code(self) -> ExitCode {
+        ExitCode::from(match self {
+            Self::Ok => 0,
+            Self::ParseError => 1,
+            Self::Warnings => 2,
+        })
+    }
+}
+
+fn check(path: &str, write: bool) -> Outcome {
+    let txt = match std::fs::read_to_string(path) {
+        Ok(txt) => txt,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return Outcome::ParseError;
+        }
+    };
+
+    let recipe = match txt.parse::<Recipe>() {
+        Ok(recipe) => recipe,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return Outcome::ParseError;
+        }
+    };
+
+    let warnings = recipe.validate();
+    for warning in &warnings {
+        eprintln!("{path}: warning: {warning}");
+    }
+
+    let formatted = recipe.to_recipe_txt();
+    if write && txt.lines().any(is_comment) {
+        eprintln!("{path}: not rewritten, formatting would drop its comments");
+    } else if write && formatted != txt {
+        match std::fs::write(path, formatted) {
+            Ok(()) => println!("formatted {path}"),
+            Err(e) => eprintln!("{path}: {e}"),
+        }
+    }
+
+    match warnings.is_empty() {
+        true => Outcome::Ok,
+        false => Outcome::Warnings,
+    }
+}
+
+fn main() -> ExitCode {
+    let mut write = false;
+    let mut paths = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--write" | "-w" => write = true,
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("usage: recipe-fmt [--write] <recipe.txt>...");
+        return Outcome::ParseError.exit_code();
+    }
+
+    let worst = paths
+        .iter()
+        .map(|path| check(path, write))
+        .max()
+        .unwrap_or(Outcome::Ok);
+
+    worst.exit_code()
+}
@@ -0,0 +1,38 @@
+//! Parsing, scaling and unit conversion for recipes written in the recipe book's
+//! `.txt` format. The Leptos app is built on top of this in the binary.
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use recipe_book::{Recipe, UnitSystem};
+//!
+//! let recipe = Recipe::from_str(
+//!     "Toast
+//!
+//! ---ingredients
+//!
+//! 2 slices bread
+//! 1 tbsp butter
+//!
+//! ---steps
+//!
+//! Toast the bread and spread the butter on it.",
+//! )?;
+//!
+//! assert_eq!(recipe.title, "Toast");
+//! assert_eq!(recipe.ingredients_summary(UnitSystem::Imperial), "2 slices bread, 1 tbsp butter");
+//! # Ok::<(), recipe_book::RecipeError>(())
+//! ```
+
+pub mod measurements;
+pub mod recipe_util;
+
+pub use measurements::{
+    DecimalStyle, Duration, Imperial, ImperialUk, MeasurementError, Metric, Temperature,
+    UnitSystem, Volume, Weight,
+};
+pub use recipe_util::{
+    AisleTable, Allergen, Change, CostEstimate, IncrementalIndex, Ingredient, IngredientQuantity,
+    IngredientSection, Ingredients, Money, Price, PriceTable, Recipe, RecipeBuilder, RecipeDiff,
+    RecipeError, RecipeWarning, Rounding, Step,
+};
@@ -1,15 +1,18 @@
 #![feature(path_file_prefix)]
 
-mod measurements;
 mod recipe;
-mod recipe_util;
 
 use leptos_query::provide_query_client;
 use recipe::*;
 
-use leptos::{leptos_dom::logging::console_log, *};
+use leptos::*;
 use leptos_router::*;
-use leptos_use::{use_mouse, UseMouseReturn};
+use leptos_use::{
+    storage::use_local_storage, use_mouse, use_preferred_dark, utils::FromToStringCodec,
+    UseMouseReturn,
+};
+use recipe_book::{measurements, recipe_util, UnitSystem};
+use recipe_util::BASE_PATH;
 use stylance::import_style;
 
 import_style!(pub css, "../styles/main.module.css");
@@ -18,30 +21,63 @@ fn main() {
     console_error_panic_hook::set_once();
 
     mount_to_body(App)
-
-    // let recipes = RECIPES.iter().collect::<Vec<_>>();
-
-    // dbg!(&recipes);
-
-    // let s = include_str!("../recipes/boscaiola.txt");
-    // let recipe = s.parse::<recipe_util::Recipe>();
-    // println!("{recipe:?}")
 }
 
 #[component]
 fn App() -> impl IntoView {
     provide_query_client();
 
+    // Persisted so the choice sticks across recipes and reloads. Until there's a
+    // choice, recipe pages show recipes in the units they were written in.
+    let (unit, unit_setter, _) = use_local_storage::<UnitSystem, FromToStringCodec>(UNIT_SYSTEM_KEY);
+
+    provide_context(unit);
+    provide_context(unit_setter);
+
+    let (favorites, favorites_setter, _) =
+        use_local_storage::<Favorites, FromToStringCodec>("favorites");
+
+    provide_context(favorites);
+    provide_context(favorites_setter);
+
+    // The stylesheets pick their colors from the `data-theme` on <html>
+    let (theme, theme_setter, _) = use_local_storage::<Theme, FromToStringCodec>("theme");
+    let system_dark = use_preferred_dark();
+
+    create_effect(move |_| {
+        let name = match theme().is_dark(system_dark()) {
+            true => "dark",
+            false => "light",
+        };
+        if let Some(html) = document().document_element() {
+            let _ = html.set_attribute("data-theme", name);
+        }
+    });
+
+    provide_context(theme);
+    provide_context(theme_setter);
+
+    provide_index_ready();
+
     view! {
-        <Router>
+        <Router base=BASE_PATH>
             <nav class=css::nav>
-                <A href={"list"}>{"List of all recipes"}</A>
+                <BreadcrumbComponent/>
+                <A class=css::nav_link href="/favorites">{"Favorites"}</A>
+                <A class=css::nav_link href="/recipe/random">{"Surprise me"}</A>
+                <ThemeButtonComponent/>
             </nav>
-                <Routes>
-                    <Route path="/" view=|| view! { <Home extra={"garfsmie".into()}/> }/>
+                <Routes base=BASE_PATH.to_string()>
+                    <Route path="/" view=Home/>
                     <Route path="recipes" view=Mouse/>
+                    <Route path="recipe/random" view=RandomRecipeComponent/>
                     <Route path="recipe/:name" view=RecipePageComponent/>
+                    <Route path="recipe/:name/print" view=PrintRecipeComponent/>
+                    <Route path="recipe/:name/edit" view=EditRecipeComponent/>
+                    <Route path="recipe/tag/:tag" view=TagComponent/>
                     <Route path="list" view=RecipesComponent/>
+                    <Route path="favorites" view=FavoritesComponent/>
+                    <Route path="/*any" view=NotFound/>
             </Routes>
         </Router>
     }
@@ -57,13 +93,19 @@ fn Mouse() -> impl IntoView {
 }
 
 #[component]
-fn Home(extra: String) -> impl IntoView {
+fn Home() -> impl IntoView {
     let s = include_str!("../public/recipes/egg_fried_rice.txt");
     let recipe = s.parse::<recipe_util::Recipe>().unwrap();
 
-    console_log(&extra);
-
     view! {
         <RecipeComponent recipe/>
     }
 }
+
+#[component]
+fn NotFound() -> impl IntoView {
+    view! {
+        <h1>{"Page not found"}</h1>
+        <A href="/list">{"Back to all recipes"}</A>
+    }
+}
@@ -1,14 +1,161 @@
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::{marker::PhantomData, str::FromStr};
 use thiserror::Error;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Metric;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Imperial;
 
-#[derive(Debug, Error, Clone, Serialize, Deserialize)]
+/// British imperial units, which share weights with [`Imperial`] but have larger
+/// fluid ounces, pints and quarts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImperialUk;
+
+/// US customary units, which is what [`Imperial`] has always meant here.
+pub type UsCustomary = Imperial;
+
+/// How decimals are written, `1.5` or `1,5`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DecimalStyle {
+    #[default]
+    Point,
+    Comma,
+}
+
+impl DecimalStyle {
+    /// Wraps `value` so that it displays with this decimal separator, as
+    /// quantities' own `Display` always uses a point
+    pub const fn display<D: std::fmt::Display>(self, value: D) -> WithDecimals<D> {
+        WithDecimals { value, style: self }
+    }
+}
+
+/// Displays `value` with the decimals in `style`. Only points between two
+/// digits are decimal points, so text around the amounts is left alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithDecimals<D> {
+    pub value: D,
+    pub style: DecimalStyle,
+}
+
+impl<D: std::fmt::Display> std::fmt::Display for WithDecimals<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.value.to_string();
+
+        if self.style == DecimalStyle::Point {
+            return write!(f, "{s}");
+        }
+
+        let bytes = s.as_bytes();
+        for (i, c) in s.char_indices() {
+            let between_digits = i > 0
+                && bytes[i - 1].is_ascii_digit()
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+
+            match c {
+                '.' if between_digits => write!(f, ",")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The unit system quantities are displayed in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+    ImperialUk,
+}
+
+impl UnitSystem {
+    /// Cycles through Metric → US → UK
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Metric => Self::Imperial,
+            Self::Imperial => Self::ImperialUk,
+            Self::ImperialUk => Self::Metric,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Metric => "Metric",
+            Self::Imperial => "Imperial (US)",
+            Self::ImperialUk => "Imperial (UK)",
+        }
+    }
+
+    /// The system a quantity written like `250 ml` or `2 cups` belongs to, or
+    /// `None` for counts and units every system uses, like `tsp`
+    pub fn written_in(quantity: &str) -> Option<Self> {
+        match (Self::Metric.uses(quantity), Self::Imperial.uses(quantity)) {
+            (true, false) => Some(Self::Metric),
+            (false, true) => Some(Self::Imperial),
+            _ => None,
+        }
+    }
+
+    /// Whether a quantity written like `8 oz` or `1-2 cups` belongs to this
+    /// system. Counts and units every system uses, like `tsp`, belong to all.
+    pub fn uses(self, quantity: &str) -> bool {
+        let unit = quantity
+            .split_whitespace()
+            .filter(|w| !w.starts_with(|c: char| c.is_numeric() || c == '.'))
+            .filter(|w| !matches!(*w, "to" | "a" | "an"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        match unit.trim_end_matches('s') {
+            "mg" | "milligram" | "cg" | "centigram" | "dg" | "decigram" | "g" | "gram" | "kg"
+            | "kilogram" | "ml" | "milliliter" | "millilitre" | "cl" | "centiliter"
+            | "centilitre" | "dl" | "deciliter" | "decilitre" | "l" | "liter" | "litre" => {
+                self == Self::Metric
+            }
+            "oz" | "ounce" | "lb" | "pound" => self != Self::Metric,
+            "cup" | "floz" | "quart" => self == Self::Imperial,
+            _ => true,
+        }
+    }
+}
+
+impl std::fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Metric => write!(f, "metric"),
+            Self::Imperial => write!(f, "imperial"),
+            Self::ImperialUk => write!(f, "imperial-uk"),
+        }
+    }
+}
+
+impl FromStr for UnitSystem {
+    type Err = MeasurementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "metric" => Ok(Self::Metric),
+            "imperial" | "us" => Ok(Self::Imperial),
+            "imperial-uk" | "uk" => Ok(Self::ImperialUk),
+            _ => Err(MeasurementError::UnknownUnit),
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MeasurementError {
     #[error("String is empty")]
     EmptyString,
@@ -16,16 +163,191 @@ pub enum MeasurementError {
     UnknownUnit,
     #[error("Invalid format")]
     InvalidFormat,
+    #[error("Amount can't be negative")]
+    NegativeAmount,
+    #[error("Temperature is below absolute zero")]
+    BelowAbsoluteZero,
     #[error("{0}")]
-    CustomString(String), // #[error("{0}")]
-                          // ParseFloatError(#[from] std::num::ParseFloatError),
+    CustomString(String),
+    #[error("{0}")]
+    ParseFloat(
+        #[from]
+        #[cfg_attr(feature = "serde", serde(with = "parse_float_error"))]
+        std::num::ParseFloatError,
+    ),
+}
+
+/// `ParseFloatError` can't be built directly, so it's stored as its message and
+/// recreated by failing to parse an input that gives the same message.
+#[cfg(feature = "serde")]
+mod parse_float_error {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::num::ParseFloatError;
+
+    pub fn serialize<S: Serializer>(e: &ParseFloatError, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(e)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ParseFloatError, D::Error> {
+        let message = String::deserialize(d)?;
+        let empty = "".parse::<f64>().unwrap_err();
+
+        match message == empty.to_string() {
+            true => Ok(empty),
+            false => Ok("invalid".parse::<f64>().unwrap_err()),
+        }
+    }
+}
+
+/// Parses an amount written as a decimal (`1.5` or `1,5`), a fraction (`1/2`)
+/// or a mixed number (`1 1/2`). Negative and non-finite amounts are rejected,
+/// since they'd wrap around when stored as an unsigned quantity. Zero is
+/// allowed. A comma followed by one or two digits is a decimal comma, and commas
+/// between groups of three digits (`1,000`) are thousands separators.
+pub fn parse_amount(s: &str) -> Result<f64, MeasurementError> {
+    use MeasurementError::*;
+
+    let s = s.trim();
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let is_thousands = |s: &str| {
+        let mut groups = s.split(',');
+        let first = groups.next().unwrap_or_default();
+
+        first.len() <= 3 && is_digits(first) && groups.all(|g| g.len() == 3 && is_digits(g))
+    };
+    let rewritten;
+    let s = match s.split_once(',') {
+        Some((whole, decimals))
+            if is_digits(whole) && is_digits(decimals) && decimals.len() <= 2 =>
+        {
+            rewritten = format!("{whole}.{decimals}");
+            rewritten.as_str()
+        }
+        Some(_) if is_thousands(s) => {
+            rewritten = s.replace(',', "");
+            rewritten.as_str()
+        }
+        _ => s,
+    };
+
+    if s.starts_with('-') {
+        return Err(NegativeAmount);
+    }
+
+    if let Some((whole, fraction)) = s.split_once(' ') {
+        let whole = whole.parse::<u64>().map_err(|_| InvalidFormat)?;
+        let fraction = parse_fraction(fraction.trim()).ok_or(InvalidFormat)?;
+
+        return Ok(whole as f64 + fraction);
+    }
+
+    if s.contains('/') {
+        return parse_fraction(s).ok_or(InvalidFormat);
+    }
+
+    let amount = s.parse::<f64>()?;
+
+    match amount.is_finite() {
+        true => Ok(amount),
+        false => Err(InvalidFormat),
+    }
+}
+
+fn parse_fraction(s: &str) -> Option<f64> {
+    let (numerator, denominator) = s.split_once('/')?;
+    let numerator = numerator.parse::<u64>().ok()?;
+    let denominator = denominator.parse::<u64>().ok().filter(|&d| d != 0)?;
+
+    Some(numerator as f64 / denominator as f64)
+}
+
+/// Splits the leading amount off `s`, keeping mixed numbers like `1 1/2` whole.
+/// Returns `None` when there's nothing after the amount.
+pub fn split_amount(s: &str) -> Option<(&str, &str)> {
+    let (first, rest) = s.split_once(' ')?;
+
+    if first.parse::<u64>().is_ok() {
+        if let Some((second, after)) = rest.split_once(' ') {
+            if parse_fraction(second).is_some() {
+                let amount_end = first.len() + 1 + second.len();
+                return Some((&s[..amount_end], after));
+            }
+        }
+    }
+
+    Some((first, rest))
+}
+
+/// What a [`Quantity`] measures
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QuantityKind {
+    Weight,
+    Volume,
+}
+
+/// A measurement stored as an integer amount of a canonical unit, displayed in
+/// the unit system `T` it's tagged with. Converting between unit systems only
+/// changes the tag.
+pub trait Quantity: Copy {
+    /// The same kind of quantity in another unit system
+    type In<U: Copy>: Quantity;
+
+    fn kind(&self) -> QuantityKind;
+
+    /// The amount in the canonical unit
+    #[must_use]
+    fn raw(self) -> u64;
+
+    fn from_raw(v: u64) -> Self;
+
+    #[must_use]
+    fn scale(self, factor: f64) -> Self {
+        Self::from_raw((self.raw() as f64 * factor).round() as u64)
+    }
+
+    #[must_use]
+    fn as_metric(self) -> Self::In<Metric>;
+
+    #[must_use]
+    fn as_imperial(self) -> Self::In<Imperial>;
+
+    #[must_use]
+    fn as_imperial_uk(self) -> Self::In<ImperialUk>;
+}
+
+/// The units a quantity's `Display` writes in for one unit system, so another
+/// amount can be written in the same unit
+pub trait DisplayUnits {
+    /// How much of the raw amount one `unit` is, if it's a unit this quantity
+    /// is displayed in
+    fn unit_size(unit: &str) -> Option<u64>;
+}
+
+/// Splits the unit off the start of `s`, keeping two-word units like
+/// `rice cups` whole. The unit is returned as written.
+pub fn split_unit(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let (first, rest) = s.split_once(' ').unwrap_or((s, ""));
+
+    if first.eq_ignore_ascii_case("rice") {
+        let (second, after) = rest.split_once(' ').unwrap_or((rest, ""));
+
+        if matches!(second.to_lowercase().as_str(), "cup" | "cups") {
+            return (&s[..first.len() + 1 + second.len()], after);
+        }
+    }
+
+    (first, rest)
 }
 
 /// Weight in mg
-#[derive(
-    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
-)]
-pub struct Weight<T = Metric>(u64, PhantomData<T>);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Weight<T = Metric>(
+    u64,
+    #[cfg_attr(feature = "serde", serde(skip))] PhantomData<T>,
+);
 
 impl<T> Weight<T> {
     pub const POUND: u64 = 453_592;
@@ -43,56 +365,293 @@ impl Weight {
     pub const fn new_imperial(v: u64) -> Weight<Imperial> {
         Weight(v, PhantomData)
     }
+}
 
+impl<T> Weight<T> {
+    #[must_use]
     pub const fn get(self) -> u64 {
         self.0
     }
-}
 
-impl<T> Weight<T> {
+    /// The canonical amount in mg, whatever unit system it's displayed in
+    #[must_use]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Creates a weight from an amount in mg, like one returned by [`Self::raw`]
+    #[must_use]
+    pub const fn from_raw(v: u64) -> Self {
+        Weight(v, PhantomData)
+    }
+
+    /// The same weight, shown in imperial units. Only the unit system it's
+    /// tagged with changes, so ignoring the result is a mistake:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// let weight = recipe_book::Weight::new_metric(100_000);
+    /// weight.as_imperial();
+    /// ```
+    ///
+    /// ```
+    /// #![deny(unused_must_use)]
+    /// let weight = recipe_book::Weight::new_metric(100_000);
+    /// assert_eq!(weight.as_imperial().to_string(), "3.5 oz");
+    /// ```
+    #[must_use]
     pub const fn as_imperial(self) -> Weight<Imperial> {
         Weight(self.0, PhantomData)
     }
 
+    #[must_use]
     pub const fn as_metric(self) -> Weight<Metric> {
         Weight(self.0, PhantomData)
     }
+
+    #[must_use]
+    pub const fn as_imperial_uk(self) -> Weight<ImperialUk> {
+        Weight(self.0, PhantomData)
+    }
+
+    #[must_use]
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether both are the same amount, whatever unit system each is in
+    #[must_use]
+    pub const fn eq_value<U>(&self, other: &Weight<U>) -> bool {
+        self.0 == other.0
+    }
 }
 
-impl std::fmt::Display for Weight<Metric> {
+impl<T> std::ops::Add for Weight<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Weight(self.0 + rhs.0, PhantomData)
+    }
+}
+
+impl<T> std::ops::Sub for Weight<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Weight(self.0.saturating_sub(rhs.0), PhantomData)
+    }
+}
+
+impl<T: Copy> Quantity for Weight<T> {
+    type In<U: Copy> = Weight<U>;
+
+    fn kind(&self) -> QuantityKind {
+        QuantityKind::Weight
+    }
+
+    fn raw(self) -> u64 {
+        Weight::raw(self)
+    }
+
+    fn from_raw(v: u64) -> Self {
+        Weight::from_raw(v)
+    }
+
+    fn as_metric(self) -> Weight<Metric> {
+        Weight::as_metric(self)
+    }
+
+    fn as_imperial(self) -> Weight<Imperial> {
+        Weight::as_imperial(self)
+    }
+
+    fn as_imperial_uk(self) -> Weight<ImperialUk> {
+        Weight::as_imperial_uk(self)
+    }
+}
+
+impl<T> std::ops::Mul<f64> for Weight<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Weight((self.0 as f64 * rhs).round() as u64, PhantomData)
+    }
+}
+
+impl<T> std::ops::Div<f64> for Weight<T> {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Weight((self.0 as f64 / rhs).round() as u64, PhantomData)
+    }
+}
+
+impl<T> Weight<T> {
+    /// Converts to a volume, given a density in g/mL
+    #[must_use]
+    pub fn to_volume(&self, density: f64) -> Volume<T> {
+        Volume((self.0 as f64 / density).round() as u64, PhantomData)
+    }
+}
+
+/// `amount` in units of `unit`, rounded to one decimal for the large amounts
+/// at the top of each unit's range. Written with `{}`, whole amounts like `12`
+/// drop the decimal while `7.9` keeps it.
+fn large_amount(amount: u64, unit: u64) -> f64 {
+    (amount as f64 * 10. / unit as f64).round() / 10.
+}
+
+/// The unit word to write after `amount`, `singular` when the amount as it's
+/// written reads as exactly one, like `1.0 cup`, and `plural` otherwise.
+fn unit_word(amount: &str, singular: &'static str, plural: &'static str) -> &'static str {
+    if amount.parse::<f64>() == Ok(1.) {
+        singular
+    } else {
+        plural
+    }
+}
+
+/// A metric amount in the unit it reads best in, as picked by
+/// [`Weight::normalize_display`] and [`Volume::normalize_display`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalizedAmount {
+    pub amount: f64,
+    pub unit: &'static str,
+    /// How many decimals to write, or `None` for only as many as it needs
+    pub decimals: Option<usize>,
+}
+
+impl std::fmt::Display for NormalizedAmount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.decimals {
+            Some(decimals) => write!(f, "{:.*} {}", decimals, self.amount, self.unit),
+            None => write!(f, "{} {}", self.amount, self.unit),
+        }
+    }
+}
+
+impl NormalizedAmount {
+    const fn new(amount: f64, unit: &'static str, decimals: Option<usize>) -> Self {
+        Self {
+            amount,
+            unit,
+            decimals,
+        }
+    }
+}
+
+/// Formats a metric weight. In precise mode grams are never rounded away, so
+/// small amounts keep a decimal (`1.2 g`) and large ones stay in grams
+/// (`1250 g`) instead of switching to kilograms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightDisplay {
+    pub weight: Weight<Metric>,
+    pub precise: bool,
+}
+
+impl Weight<Metric> {
+    /// Picks the unit the weight reads best in: milligrams under a gram, whole
+    /// grams under a kilogram and kilograms with a decimal past that
+    #[must_use]
+    pub fn normalize_display(self) -> NormalizedAmount {
         match self.0 {
-            0 => write!(f, "0 g"),
-            n @ 0..1000 => write!(f, "{n} mg"),
-            n @ 1000..1_000_000 => write!(f, "{} g", n / 1000),
-            n @ 1_000_000..10_000_000 => write!(f, "{:.1} kg", n as f64 / 1_000_000.),
-            n @ 10_000_000.. => write!(f, "{} kg", n / 1_000_000),
+            0 => NormalizedAmount::new(0., "g", Some(0)),
+            n @ 1..1000 => NormalizedAmount::new(n as f64, "mg", Some(0)),
+            n @ 1000..999_500 => NormalizedAmount::new((n as f64 / 1000.).round(), "g", Some(0)),
+            n @ 999_500..10_000_000 => NormalizedAmount::new(n as f64 / 1_000_000., "kg", Some(1)),
+            n @ 10_000_000.. => NormalizedAmount::new(large_amount(n, 1_000_000), "kg", None),
+        }
+    }
+
+    /// Displays the weight with at least gram precision, for baking
+    #[must_use]
+    pub const fn precise(self) -> WeightDisplay {
+        WeightDisplay {
+            weight: self,
+            precise: true,
+        }
+    }
+}
+
+impl std::fmt::Display for WeightDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.weight.0, self.precise) {
+            (n @ 1000..10_000, true) => write!(f, "{:.1} g", n as f64 / 1000.),
+            (n @ 10_000.., true) => write!(f, "{} g", (n as f64 / 1000.).round()),
+            _ => write!(f, "{}", self.weight.normalize_display()),
+        }
+    }
+}
+
+/// The alternate form, `{:#}`, uses the precise display.
+impl std::fmt::Display for Weight<Metric> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        WeightDisplay {
+            weight: *self,
+            precise: f.alternate(),
         }
+        .fmt(f)
     }
 }
 
+/// Amounts too small for the smallest imperial fraction fall back to milligrams,
+/// so only an actual zero renders as `0 oz`.
 impl std::fmt::Display for Weight<Imperial> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.0 {
-            0..250 => write!(f, "0 oz"),
+            0 => write!(f, "0 oz"),
+            n @ 1..250 => write!(f, "{n} mg"),
             250..500 => write!(f, "1/8 tsp"),
             500..1000 => write!(f, "1/4 tsp"),
             1000..2000 => write!(f, "1/2 tsp"),
             2000..4000 => write!(f, "1 tsp"),
             4000..8000 => write!(f, "1/2 tbsp"),
             8000..12000 => write!(f, "1 tbsp"),
-            n @ 12000..Self::OUNCE => write!(f, "{:.1} oz", n as f64 / Self::OUNCE as f64),
-            n @ Self::OUNCE..Self::OUNCE_LIMIT => {
-                write!(f, "{:.1} g", n as f64 / Self::OUNCE as f64)
+            n @ 12000..Self::OUNCE_LIMIT => {
+                write!(f, "{:.1} oz", n as f64 / Self::OUNCE as f64)
             }
             n @ Self::OUNCE_LIMIT..Self::POUND_LIMIT => {
-                write!(f, "{:.1} g", n as f64 / Self::POUND as f64)
+                write!(f, "{:.1} lb", n as f64 / Self::POUND as f64)
             }
-            n @ Self::POUND_LIMIT.. => write!(f, "{} g", n / Self::POUND),
+            n @ Self::POUND_LIMIT.. => write!(f, "{} lb", large_amount(n, Self::POUND)),
+        }
+    }
+}
+
+impl std::fmt::Display for Weight<ImperialUk> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_imperial())
+    }
+}
+
+impl DisplayUnits for Weight<Metric> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        match unit {
+            "mg" => Some(1),
+            "g" => Some(1000),
+            "kg" => Some(1_000_000),
+            _ => None,
+        }
+    }
+}
+
+impl DisplayUnits for Weight<Imperial> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        match unit {
+            "mg" => Some(1),
+            "oz" => Some(Self::OUNCE),
+            "lb" => Some(Self::POUND),
+            _ => None,
         }
     }
 }
 
+impl DisplayUnits for Weight<ImperialUk> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        Weight::<Imperial>::unit_size(unit)
+    }
+}
+
 impl FromStr for Weight {
     type Err = MeasurementError;
 
@@ -103,20 +662,12 @@ impl FromStr for Weight {
             return Err(EmptyString);
         }
 
-        let (amount, last) = s.split_once(' ').ok_or(InvalidFormat)?;
+        let (amount, last) = split_amount(s).ok_or(InvalidFormat)?;
 
-        let unit = last
-            .split_once(' ')
-            .map(|(u, _)| u)
-            .unwrap_or(last)
-            .trim()
-            .to_lowercase();
+        let unit = split_unit(last).0.to_lowercase();
         let unit = unit.as_str().trim_end_matches('s');
 
-        let amount = amount
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| CustomString(e.to_string()))?;
+        let amount = parse_amount(amount)?;
 
         let weight = match unit {
             "mg" | "milligram" => amount,
@@ -129,17 +680,39 @@ impl FromStr for Weight {
             _ => return Err(UnknownUnit),
         };
 
-        Ok(Weight(weight as u64, PhantomData))
+        Ok(Weight(weight.round() as u64, PhantomData))
+    }
+}
+
+impl TryFrom<&str> for Weight {
+    type Error = MeasurementError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Weight {
+    type Error = MeasurementError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
     }
 }
 
 /// Volume in 1/1000 mL
-#[derive(
-    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
-)]
-pub struct Volume<T = Metric>(u64, PhantomData<T>);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Volume<T = Metric>(
+    u64,
+    #[cfg_attr(feature = "serde", serde(skip))] PhantomData<T>,
+);
 
 impl<T> Volume<T> {
+    pub const SMIDGEN: u64 = Self::TSP / 32;
+    pub const PINCH: u64 = Self::TSP / 16;
+    pub const DASH: u64 = Self::TSP / 8;
     pub const TSP: u64 = 4_928;
     pub const TBSP: u64 = 14_786;
     pub const OUNCE: u64 = 29_573;
@@ -147,17 +720,45 @@ impl<T> Volume<T> {
     pub const CUP: u64 = 236_588;
     pub const QUART: u64 = 946_353;
 
-    const LOWEST_LIMIT: u64 = Self::TSP / 15;
-    const E_TSP_LIMIT: u64 = Self::TSP * 12 / 80;
-    const Q_TSP_LIMIT: u64 = Self::TSP * 12 / 40;
-    const H_TSP_LIMIT: u64 = Self::TSP * 12 / 20;
-    const TQ_TSP_LIMIT: u64 = Self::TSP * 120000 / 133333;
-    const TSP_LIMIT: u64 = Self::TSP * 12 / 10;
-    const H_TBSP_LIMIT: u64 = Self::TBSP * 12 / 20;
-    const TBSP_LIMIT: u64 = Self::TBSP * 12 / 10;
+    pub const UK_OUNCE: u64 = 28_413;
+    pub const UK_CUP: u64 = 284_131;
+    pub const UK_PINT: u64 = 568_261;
+    pub const UK_QUART: u64 = 1_136_523;
+
+    /// The measures small volumes snap to, smallest first
+    const SPOONS: [(u64, &'static str); 9] = [
+        (Self::SMIDGEN, "1 smidgen"),
+        (Self::PINCH, "1 pinch"),
+        (Self::TSP / 8, "1/8 tsp"),
+        (Self::TSP / 4, "1/4 tsp"),
+        (Self::TSP / 2, "1/2 tsp"),
+        (Self::TSP * 3 / 4, "3/4 tsp"),
+        (Self::TSP, "1 tsp"),
+        (Self::TBSP / 2, "1/2 tbsp"),
+        (Self::TBSP, "1 tbsp"),
+    ];
+    /// Halfway between a tbsp and a floz, past which volumes are given in floz
+    const SPOON_LIMIT: u64 = (Self::TBSP + Self::OUNCE) / 2;
     const OUNCE_LIMIT: u64 = Self::OUNCE * 8;
     const CUP_LIMIT: u64 = Self::QUART * 190 / 200;
+    /// The fractions of a cup measuring cups come in, after the whole cups
+    const CUP_FRACTIONS: [(u64, &'static str); 7] = [
+        (0, ""),
+        (Self::CUP / 4, "1/4"),
+        (Self::CUP / 3, "1/3"),
+        (Self::CUP / 2, "1/2"),
+        (Self::CUP * 2 / 3, "2/3"),
+        (Self::CUP * 3 / 4, "3/4"),
+        (Self::CUP, ""),
+    ];
+    /// How far off a cup fraction a volume can be and still be shown as one,
+    /// about a teaspoon
+    const CUP_FRACTION_TOLERANCE: u64 = Self::CUP / 48;
     const QUART_LIMIT: u64 = Self::QUART * 5;
+
+    const UK_OUNCE_LIMIT: u64 = Self::UK_OUNCE * 10;
+    const UK_PINT_LIMIT: u64 = Self::UK_QUART * 190 / 200;
+    const UK_QUART_LIMIT: u64 = Self::UK_QUART * 5;
 }
 
 impl Volume {
@@ -168,84 +769,305 @@ impl Volume {
     pub const fn new_imperial(v: u64) -> Volume<Imperial> {
         Volume(v, PhantomData)
     }
+}
 
+impl<T> Volume<T> {
+    #[must_use]
     pub const fn get(self) -> u64 {
         self.0
     }
-}
 
-impl<T> Volume<T> {
+    /// The canonical amount in 1/1000 mL, whatever unit system it's displayed in
+    #[must_use]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Creates a volume from an amount in 1/1000 mL, like one returned by [`Self::raw`]
+    #[must_use]
+    pub const fn from_raw(v: u64) -> Self {
+        Volume(v, PhantomData)
+    }
+
+    /// The same volume, shown in imperial units
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// let volume = recipe_book::Volume::new_metric(250_000);
+    /// volume.as_imperial();
+    /// ```
+    #[must_use]
     pub const fn as_imperial(self) -> Volume<Imperial> {
         Volume(self.0, PhantomData)
     }
 
+    #[must_use]
     pub const fn as_metric(self) -> Volume<Metric> {
         Volume(self.0, PhantomData)
     }
-}
 
-impl std::fmt::Display for Volume<Metric> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            0..500 => write!(f, "0 ml"),
-            n @ 500..500_000 => write!(f, "{} ml", n / 1000),
-            n @ 500_000..5_000_000 => write!(f, "{:.1} l", n as f64 / 1_000_000 as f64),
-            n @ 5_000_000.. => write!(f, "{} l", n / 1_000_000),
-        }
+    #[must_use]
+    pub const fn as_imperial_uk(self) -> Volume<ImperialUk> {
+        Volume(self.0, PhantomData)
+    }
+
+    #[must_use]
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether both are the same amount, whatever unit system each is in
+    #[must_use]
+    pub const fn eq_value<U>(&self, other: &Volume<U>) -> bool {
+        self.0 == other.0
     }
 }
 
-impl std::fmt::Display for Volume<Imperial> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            0..Self::LOWEST_LIMIT => write!(f, "0 tsp"),
-            Self::LOWEST_LIMIT..Self::E_TSP_LIMIT => write!(f, "1/8 tsp"),
-            Self::E_TSP_LIMIT..Self::Q_TSP_LIMIT => write!(f, "1/4 tsp"),
-            Self::Q_TSP_LIMIT..Self::H_TSP_LIMIT => write!(f, "1/2 tsp"),
-            Self::H_TSP_LIMIT..Self::TQ_TSP_LIMIT => write!(f, "3/4 tsp"),
-            Self::TQ_TSP_LIMIT..Self::TSP_LIMIT => write!(f, "1 tsp"),
-            Self::TSP_LIMIT..Self::H_TBSP_LIMIT => write!(f, "1/2 tbsp"),
-            Self::H_TBSP_LIMIT..Self::TBSP_LIMIT => write!(f, "1 tbsp"),
-            n @ Self::TBSP_LIMIT..Self::OUNCE_LIMIT => {
-                write!(f, "{:.1} floz", n as f64 / Self::OUNCE as f64)
-            }
-            n @ Self::OUNCE_LIMIT..Self::CUP_LIMIT => {
-                write!(f, "{:.1} cups", n as f64 / Self::CUP as f64)
-            }
-            n @ Self::CUP_LIMIT..Self::QUART_LIMIT => {
-                write!(f, "{:.1} quarts", (n as f64 / Self::QUART as f64))
-            }
-            n @ Self::QUART_LIMIT.. => write!(f, "{} quarts", n / Self::QUART),
-        }
+impl<T> std::ops::Add for Volume<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Volume(self.0 + rhs.0, PhantomData)
     }
 }
 
-impl FromStr for Volume {
-    type Err = MeasurementError;
+impl<T> std::ops::Sub for Volume<T> {
+    type Output = Self;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use MeasurementError::*;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Volume(self.0.saturating_sub(rhs.0), PhantomData)
+    }
+}
 
-        if s.is_empty() {
-            return Err(EmptyString);
-        }
+impl<T: Copy> Quantity for Volume<T> {
+    type In<U: Copy> = Volume<U>;
 
-        let (amount, last) = s.split_once(' ').ok_or(InvalidFormat)?;
+    fn kind(&self) -> QuantityKind {
+        QuantityKind::Volume
+    }
 
-        let unit = last
-            .split_once(' ')
-            .map(|(u, _)| u)
-            .unwrap_or(last)
-            .trim()
-            .to_lowercase();
+    fn raw(self) -> u64 {
+        Volume::raw(self)
+    }
+
+    fn from_raw(v: u64) -> Self {
+        Volume::from_raw(v)
+    }
+
+    fn as_metric(self) -> Volume<Metric> {
+        Volume::as_metric(self)
+    }
+
+    fn as_imperial(self) -> Volume<Imperial> {
+        Volume::as_imperial(self)
+    }
+
+    fn as_imperial_uk(self) -> Volume<ImperialUk> {
+        Volume::as_imperial_uk(self)
+    }
+}
+
+impl<T> std::ops::Mul<f64> for Volume<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Volume((self.0 as f64 * rhs).round() as u64, PhantomData)
+    }
+}
+
+impl<T> std::ops::Div<f64> for Volume<T> {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Volume((self.0 as f64 / rhs).round() as u64, PhantomData)
+    }
+}
+
+impl<T> Volume<T> {
+    /// Converts to a weight, given a density in g/mL
+    #[must_use]
+    pub fn to_weight(&self, density: f64) -> Weight<T> {
+        Weight((self.0 as f64 * density).round() as u64, PhantomData)
+    }
+}
+
+impl Volume<Metric> {
+    /// Picks the unit the volume reads best in: millilitres up to half a litre
+    /// and litres past that. Amounts under a millilitre keep one decimal, or as
+    /// many as it takes not to round to zero, so only an actual zero becomes
+    /// `0 ml`.
+    #[must_use]
+    pub fn normalize_display(self) -> NormalizedAmount {
+        match self.0 {
+            0 => NormalizedAmount::new(0., "ml", Some(0)),
+            n @ 1..5 => NormalizedAmount::new(n as f64 / 1000., "ml", Some(3)),
+            n @ 5..50 => NormalizedAmount::new(n as f64 / 1000., "ml", Some(2)),
+            n @ 50..1000 => NormalizedAmount::new(n as f64 / 1000., "ml", Some(1)),
+            n @ 1000..499_500 => NormalizedAmount::new((n as f64 / 1000.).round(), "ml", Some(0)),
+            n @ 499_500..5_000_000 => NormalizedAmount::new(n as f64 / 1_000_000., "l", Some(1)),
+            n @ 5_000_000.. => NormalizedAmount::new(large_amount(n, 1_000_000), "l", None),
+        }
+    }
+}
+
+impl std::fmt::Display for Volume<Metric> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.normalize_display())
+    }
+}
+
+impl Volume<Imperial> {
+    /// Writes volumes from a quarter cup up to the cup limit as whole cups and
+    /// a measuring cup fraction, like `1 1/3 cups`, if one is close enough
+    fn cup_fraction(self) -> Option<String> {
+        let range = Self::CUP / 4 - Self::CUP_FRACTION_TOLERANCE..Self::CUP_LIMIT;
+        if !range.contains(&self.0) {
+            return None;
+        }
+
+        let (whole, rest) = (self.0 / Self::CUP, self.0 % Self::CUP);
+        let (amount, fraction) = Self::CUP_FRACTIONS
+            .iter()
+            .min_by_key(|(amount, _)| amount.abs_diff(rest))
+            .expect("There are cup fractions");
+
+        if amount.abs_diff(rest) > Self::CUP_FRACTION_TOLERANCE {
+            return None;
+        }
+
+        let whole = whole + amount / Self::CUP;
+        Some(match (whole, *fraction) {
+            (0, fraction) => format!("{fraction} cup"),
+            (1, "") => "1 cup".to_string(),
+            (whole, "") => format!("{whole} cups"),
+            (whole, fraction) => format!("{whole} {fraction} cups"),
+        })
+    }
+}
+
+/// Amounts under 1/8 tsp are shown as a smidgen or a pinch, whichever is closer,
+/// so only an actual zero renders as `0 tsp`. Amounts close to a measuring cup
+/// are shown as one, like `1/3 cup`.
+impl std::fmt::Display for Volume<Imperial> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(cups) = self.cup_fraction() {
+            return write!(f, "{cups}");
+        }
+
+        match self.0 {
+            0 => write!(f, "0 tsp"),
+            // The closest spoon measure, the smaller one when exactly halfway
+            n @ 1..Self::SPOON_LIMIT => {
+                let (_, spoon) = Self::SPOONS
+                    .iter()
+                    .min_by_key(|(amount, _)| amount.abs_diff(n))
+                    .expect("There are spoon measures");
+
+                write!(f, "{spoon}")
+            }
+            n @ Self::SPOON_LIMIT..Self::OUNCE_LIMIT => {
+                write!(f, "{:.1} floz", n as f64 / Self::OUNCE as f64)
+            }
+            n @ Self::OUNCE_LIMIT..Self::CUP_LIMIT => {
+                let cups = format!("{:.1}", n as f64 / Self::CUP as f64);
+                write!(f, "{cups} {}", unit_word(&cups, "cup", "cups"))
+            }
+            n @ Self::CUP_LIMIT..Self::QUART_LIMIT => {
+                let quarts = format!("{:.1}", n as f64 / Self::QUART as f64);
+                write!(f, "{quarts} {}", unit_word(&quarts, "quart", "quarts"))
+            }
+            n @ Self::QUART_LIMIT.. => {
+                let quarts = large_amount(n, Self::QUART).to_string();
+                write!(f, "{quarts} {}", unit_word(&quarts, "quart", "quarts"))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Volume<ImperialUk> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            n @ 0..Self::SPOON_LIMIT => write!(f, "{}", Volume::new_imperial(n)),
+            n @ Self::SPOON_LIMIT..Self::UK_OUNCE_LIMIT => {
+                write!(f, "{:.1} floz", n as f64 / Self::UK_OUNCE as f64)
+            }
+            n @ Self::UK_OUNCE_LIMIT..Self::UK_PINT_LIMIT => {
+                let pints = format!("{:.1}", n as f64 / Self::UK_PINT as f64);
+                write!(f, "{pints} {}", unit_word(&pints, "pint", "pints"))
+            }
+            n @ Self::UK_PINT_LIMIT..Self::UK_QUART_LIMIT => {
+                let quarts = format!("{:.1}", n as f64 / Self::UK_QUART as f64);
+                write!(f, "{quarts} {}", unit_word(&quarts, "quart", "quarts"))
+            }
+            n @ Self::UK_QUART_LIMIT.. => {
+                let quarts = large_amount(n, Self::UK_QUART).to_string();
+                write!(f, "{quarts} {}", unit_word(&quarts, "quart", "quarts"))
+            }
+        }
+    }
+}
+
+impl DisplayUnits for Volume<Metric> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        match unit {
+            "ml" => Some(1000),
+            "l" => Some(1_000_000),
+            _ => None,
+        }
+    }
+}
+
+impl DisplayUnits for Volume<Imperial> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        match unit {
+            "tsp" => Some(Self::TSP),
+            "tbsp" => Some(Self::TBSP),
+            "floz" => Some(Self::OUNCE),
+            "cup" | "cups" => Some(Self::CUP),
+            "quart" | "quarts" => Some(Self::QUART),
+            _ => None,
+        }
+    }
+}
+
+impl DisplayUnits for Volume<ImperialUk> {
+    fn unit_size(unit: &str) -> Option<u64> {
+        match unit {
+            "tsp" => Some(Self::TSP),
+            "tbsp" => Some(Self::TBSP),
+            "floz" => Some(Self::UK_OUNCE),
+            "pint" | "pints" => Some(Self::UK_PINT),
+            "quart" | "quarts" => Some(Self::UK_QUART),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Volume {
+    type Err = MeasurementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use MeasurementError::*;
+
+        if s.is_empty() {
+            return Err(EmptyString);
+        }
+
+        let (amount, last) = split_amount(s).ok_or(InvalidFormat)?;
+
+        let unit = split_unit(last).0.to_lowercase();
         let unit = unit.as_str().trim_end_matches('s');
 
-        let amount = amount
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| CustomString(e.to_string()))?;
+        // "a pinch of salt"
+        let amount = match amount.trim() {
+            "a" | "an" => 1.0,
+            amount => parse_amount(amount)?,
+        };
 
         let volume = match unit {
+            "smidgen" => amount * Self::SMIDGEN as f64,
+            "pinch" | "pinche" => amount * Self::PINCH as f64,
+            "dash" | "dashe" => amount * Self::DASH as f64,
             "ml" | "milliliter" | "millilitre" => amount * 1_000.0,
             "cl" | "centiliter" | "centilitre" => amount * 10_000.0,
             "dl" | "deciliter" | "decilitre" => amount * 100_000.0,
@@ -253,13 +1075,342 @@ impl FromStr for Volume {
             "tsp" => amount * Self::TSP as f64,
             "tbsp" => amount * Self::TBSP as f64,
             "floz" => amount * Self::OUNCE as f64,
-            "rice" if s.contains("cup") => amount * Self::RICE_CUP as f64,
+            "rice cup" => amount * Self::RICE_CUP as f64,
             "cup" => amount * Self::CUP as f64,
             "quart" => amount * Self::QUART as f64,
             _ => return Err(UnknownUnit),
         };
 
-        Ok(Volume(volume as u64, PhantomData))
+        Ok(Volume(volume.round() as u64, PhantomData))
+    }
+}
+
+impl TryFrom<&str> for Volume {
+    type Error = MeasurementError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Volume {
+    type Error = MeasurementError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Densities of common ingredients in g/mL
+pub const DENSITIES: &[(&str, f64)] = &[
+    ("water", 1.0),
+    ("milk", 1.03),
+    ("cream", 1.01),
+    ("butter", 0.91),
+    ("oil", 0.92),
+    ("honey", 1.42),
+    ("flour", 0.53),
+    ("sugar", 0.85),
+    ("brown sugar", 0.72),
+    ("powdered sugar", 0.56),
+    ("salt", 1.2),
+    ("rice", 0.85),
+    ("oats", 0.41),
+    ("cocoa", 0.42),
+];
+
+/// Looks up the density of an ingredient in g/mL by the most specific entry in
+/// [`DENSITIES`] its name ends with, so "sesame oil" is oil and "brown sugar"
+/// isn't plain sugar. Only the head noun counts: "rice vinegar" isn't rice.
+pub fn density_of(ingredient: &str) -> Option<f64> {
+    let name = format!(" {}", ingredient.trim().to_lowercase());
+
+    DENSITIES
+        .iter()
+        .filter(|(key, _)| name.ends_with(&format!(" {key}")))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, density)| *density)
+}
+
+/// Temperature in mK
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Temperature<T = Metric>(
+    u64,
+    #[cfg_attr(feature = "serde", serde(skip))] PhantomData<T>,
+);
+
+impl<T> Temperature<T> {
+    pub const ZERO_CELSIUS: u64 = 273_150;
+
+    pub fn celsius(self) -> f64 {
+        (self.0 as f64 - Self::ZERO_CELSIUS as f64) / 1000.0
+    }
+
+    pub fn fahrenheit(self) -> f64 {
+        self.celsius() * 9.0 / 5.0 + 32.0
+    }
+}
+
+impl Temperature {
+    pub const fn new_metric(v: u64) -> Temperature<Metric> {
+        Temperature(v, PhantomData)
+    }
+
+    pub const fn new_imperial(v: u64) -> Temperature<Imperial> {
+        Temperature(v, PhantomData)
+    }
+}
+
+impl<T> Temperature<T> {
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// The canonical temperature in mK, whatever unit system it's displayed in
+    #[must_use]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Creates a temperature from an amount in mK, like one returned by [`Self::raw`]
+    #[must_use]
+    pub const fn from_raw(v: u64) -> Self {
+        Temperature(v, PhantomData)
+    }
+
+    #[must_use]
+    pub const fn as_imperial(self) -> Temperature<Imperial> {
+        Temperature(self.0, PhantomData)
+    }
+
+    #[must_use]
+    pub const fn as_metric(self) -> Temperature<Metric> {
+        Temperature(self.0, PhantomData)
+    }
+}
+
+impl std::fmt::Display for Temperature<Metric> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}°C", self.celsius())
+    }
+}
+
+impl std::fmt::Display for Temperature<Imperial> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}°F", self.fahrenheit())
+    }
+}
+
+impl FromStr for Temperature {
+    type Err = MeasurementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use MeasurementError::*;
+
+        let s = s.trim();
+
+        if s.is_empty() {
+            return Err(EmptyString);
+        }
+
+        let unit_i = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+            .ok_or(InvalidFormat)?;
+
+        let amount = s[..unit_i].parse::<f64>()?;
+
+        let unit = s[unit_i..]
+            .trim_start()
+            .trim_start_matches('°')
+            .to_lowercase();
+
+        let celsius = match unit.as_str() {
+            "c" | "celsius" => amount,
+            "f" | "fahrenheit" => (amount - 32.0) * 5.0 / 9.0,
+            _ => return Err(UnknownUnit),
+        };
+
+        let temperature = celsius * 1000.0 + Self::ZERO_CELSIUS as f64;
+        if temperature < 0.0 {
+            return Err(BelowAbsoluteZero);
+        }
+
+        Ok(Temperature(temperature.round() as u64, PhantomData))
+    }
+}
+
+/// Duration in seconds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Duration(u64);
+
+impl Duration {
+    pub const MINUTE: u64 = 60;
+    pub const HOUR: u64 = 3600;
+    pub const DAY: u64 = Self::HOUR * 24;
+
+    pub const fn from_secs(v: u64) -> Self {
+        Duration(v)
+    }
+
+    pub const fn from_minutes(v: u64) -> Self {
+        Duration(v * Self::MINUTE)
+    }
+
+    pub const fn as_secs(self) -> u64 {
+        self.0
+    }
+
+    pub const fn as_minutes(self) -> u64 {
+        self.0 / Self::MINUTE
+    }
+
+    /// Formats like a kitchen timer, `20:00` or `1:30:00`
+    pub fn to_clock(self) -> String {
+        let hours = self.0 / Self::HOUR;
+        let minutes = self.0 % Self::HOUR / Self::MINUTE;
+        let seconds = self.0 % Self::MINUTE;
+
+        match hours {
+            0 => format!("{minutes}:{seconds:02}"),
+            _ => format!("{hours}:{minutes:02}:{seconds:02}"),
+        }
+    }
+
+    /// Formats as an ISO 8601 duration, like `PT20M` or `PT1H30M`
+    pub fn to_iso8601(self) -> String {
+        let hours = self.0 / Self::HOUR;
+        let minutes = self.0 % Self::HOUR / Self::MINUTE;
+        let seconds = self.0 % Self::MINUTE;
+
+        let mut iso = String::from("PT");
+        if hours > 0 {
+            iso.push_str(&format!("{hours}H"));
+        }
+        if minutes > 0 {
+            iso.push_str(&format!("{minutes}M"));
+        }
+        if seconds > 0 || self.0 == 0 {
+            iso.push_str(&format!("{seconds}S"));
+        }
+
+        iso
+    }
+}
+
+impl std::ops::Add for Duration {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Duration(self.0 + rhs.0)
+    }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hours = self.0 / Self::HOUR;
+        let minutes = self.0 % Self::HOUR / Self::MINUTE;
+        let seconds = self.0 % Self::MINUTE;
+
+        let mut parts = Vec::new();
+        if hours > 0 {
+            parts.push(format!("{hours}h"));
+        }
+        if minutes > 0 || (hours == 0 && seconds == 0) {
+            parts.push(format!("{minutes}m"));
+        }
+        if seconds > 0 {
+            parts.push(format!("{seconds}s"));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Duration {
+    /// Parses an ISO 8601 duration like `PT1H30M` or `P1DT2H`. Only days and
+    /// smaller are supported, as months and years have no fixed length.
+    pub fn from_iso8601(s: &str) -> Result<Self, MeasurementError> {
+        use MeasurementError::*;
+
+        let s = s.trim().to_lowercase();
+        let period = s.strip_prefix('p').ok_or(InvalidFormat)?;
+        let (days, time) = period.split_once('t').unwrap_or((period, ""));
+
+        let is_iso_time = |c: char| c.is_ascii_digit() || matches!(c, '.' | 'h' | 'm' | 's');
+        if (days.is_empty() && time.is_empty()) || !time.chars().all(is_iso_time) {
+            return Err(InvalidFormat);
+        }
+
+        let days = match days {
+            "" => 0.0,
+            days => days
+                .strip_suffix('d')
+                .ok_or(InvalidFormat)?
+                .parse::<f64>()?,
+        };
+        let time = match time {
+            "" => Duration(0),
+            time => time.parse()?,
+        };
+
+        Ok(Duration((days * Self::DAY as f64).round() as u64 + time.0))
+    }
+}
+
+/// Parses durations written like `1h 30m`, `90 min` or `2 hours`, as well as
+/// ISO 8601 ones like `PT1H30M`.
+impl FromStr for Duration {
+    type Err = MeasurementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use MeasurementError::*;
+
+        let s = s.trim().to_lowercase();
+
+        if s.is_empty() {
+            return Err(EmptyString);
+        }
+
+        if s.starts_with('p') {
+            return Self::from_iso8601(&s);
+        }
+
+        let mut rest = s.as_str();
+        let mut seconds = 0.0;
+
+        while !rest.is_empty() {
+            let unit_i = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+
+            if unit_i == 0 {
+                return Err(InvalidFormat);
+            }
+
+            let amount = rest[..unit_i].parse::<f64>()?;
+
+            let unit_rest = rest[unit_i..].trim_start();
+            let unit_end = unit_rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(unit_rest.len());
+
+            let multiplier = match &unit_rest[..unit_end] {
+                "h" | "hr" | "hrs" | "hour" | "hours" => Self::HOUR,
+                "m" | "min" | "mins" | "minute" | "minutes" => Self::MINUTE,
+                "s" | "sec" | "secs" | "second" | "seconds" => 1,
+                "" => return Err(InvalidFormat),
+                _ => return Err(UnknownUnit),
+            };
+
+            seconds += amount * multiplier as f64;
+            rest = unit_rest[unit_end..].trim_start();
+        }
+
+        Ok(Duration(seconds.round() as u64))
     }
 }
 
@@ -272,7 +1423,7 @@ mod tests {
         let w = Volume::new_metric(900000);
         let i = w.as_imperial();
 
-        println!("{i}");
+        assert_eq!(i.to_string(), "1.0 quart");
     }
 
     #[test]
@@ -288,4 +1439,671 @@ mod tests {
         );
         assert_eq!(c.parse::<Weight>().unwrap().get(), 10_000_000_000);
     }
+
+    #[test]
+    fn print_imperial_weight() {
+        let ounces = Weight::new_metric(100_000).as_imperial();
+        let almost_two_pounds = Weight::new_metric(900_000).as_imperial();
+        let two_pounds = Weight::new_imperial(2 * Weight::<Imperial>::POUND);
+        let ten_pounds = Weight::new_imperial(10 * Weight::<Imperial>::POUND);
+
+        assert_eq!(ounces.to_string(), "3.5 oz");
+        assert_eq!(almost_two_pounds.to_string(), "2.0 lb");
+        assert_eq!(two_pounds.to_string(), "2.0 lb");
+        assert_eq!(ten_pounds.to_string(), "10 lb");
+    }
+
+    #[test]
+    fn parse_temperature() {
+        let c = "200 C".parse::<Temperature>().unwrap();
+        let degree_c = "200°C".parse::<Temperature>().unwrap();
+        let f = "350 F".parse::<Temperature>().unwrap();
+        let degree_f = "350°F".parse::<Temperature>().unwrap();
+
+        assert_eq!(c.get(), 473_150);
+        assert_eq!(c, degree_c);
+        assert_eq!(f, degree_f);
+        assert_eq!(f.to_string(), "177°C");
+        assert!("200 K".parse::<Temperature>().is_err());
+        assert_eq!(
+            "-300 C".parse::<Temperature>(),
+            Err(MeasurementError::BelowAbsoluteZero)
+        );
+        assert_eq!("-273.15 C".parse::<Temperature>().unwrap().get(), 0);
+        assert_eq!("-40 F".parse::<Temperature>().unwrap().celsius(), -40.0);
+    }
+
+    #[test]
+    fn print_temperature() {
+        let t = "200 C".parse::<Temperature>().unwrap();
+
+        assert_eq!(t.to_string(), "200°C");
+        assert_eq!(t.as_imperial().to_string(), "392°F");
+        assert_eq!(t.as_imperial().as_metric(), t);
+    }
+
+    #[test]
+    fn weight_arithmetic() {
+        let a = Weight::new_metric(1500);
+        let b = Weight::new_metric(500);
+
+        assert_eq!((a + b).get(), 2000);
+        assert_eq!((a - b).get(), 1000);
+        assert_eq!((b - a).get(), 0);
+        assert_eq!((a * 1.5).get(), 2250);
+        assert_eq!((a * 0.333).get(), 500);
+        assert_eq!((a / 2.0).get(), 750);
+        assert_eq!((b / 3.0).get(), 167);
+    }
+
+    #[test]
+    fn volume_arithmetic() {
+        let a = Volume::new_metric(1500);
+        let b = Volume::new_metric(500);
+
+        assert_eq!((a + b).get(), 2000);
+        assert_eq!((a - b).get(), 1000);
+        assert_eq!((b - a).get(), 0);
+        assert_eq!((a * 1.5).get(), 2250);
+        assert_eq!((a / 2.0).get(), 750);
+        assert_eq!((a.as_imperial() + b.as_imperial()).as_metric().get(), 2000);
+    }
+
+    #[test]
+    fn parse_duration() {
+        assert_eq!("1h 30m".parse::<Duration>().unwrap().as_minutes(), 90);
+        assert_eq!("90 min".parse::<Duration>().unwrap().as_minutes(), 90);
+        assert_eq!("45m".parse::<Duration>().unwrap().as_minutes(), 45);
+        assert_eq!("2 hours".parse::<Duration>().unwrap().as_minutes(), 120);
+        assert_eq!("30 seconds".parse::<Duration>().unwrap().as_secs(), 30);
+
+        assert!(matches!(
+            "abc".parse::<Duration>(),
+            Err(MeasurementError::InvalidFormat)
+        ));
+        assert!(matches!(
+            "90".parse::<Duration>(),
+            Err(MeasurementError::InvalidFormat)
+        ));
+        assert!(matches!(
+            "10 days".parse::<Duration>(),
+            Err(MeasurementError::UnknownUnit)
+        ));
+        assert!(matches!(
+            "".parse::<Duration>(),
+            Err(MeasurementError::EmptyString)
+        ));
+    }
+
+    #[test]
+    fn print_duration() {
+        assert_eq!(Duration::from_minutes(90).to_string(), "1h 30m");
+        assert_eq!(Duration::from_minutes(45).to_string(), "45m");
+        assert_eq!(Duration::from_minutes(60).to_string(), "1h");
+        assert_eq!(Duration::from_secs(20).to_string(), "20s");
+        assert_eq!(Duration::from_secs(0).to_string(), "0m");
+    }
+
+    #[test]
+    fn density_conversion() {
+        let water = Weight::new_metric(250_000);
+
+        assert_eq!(water.to_volume(1.0), Volume::new_metric(250_000));
+        assert_eq!(Volume::new_metric(250_000).to_weight(1.0), water);
+        assert_eq!(water.to_volume(1.0).to_string(), "250 ml");
+        assert_eq!(Volume::new_metric(100_000).to_weight(0.53).get(), 53_000);
+    }
+
+    #[test]
+    fn density_lookup() {
+        assert_eq!(density_of("Water"), Some(1.0));
+        assert_eq!(density_of("sesame oil"), Some(0.92));
+        assert_eq!(density_of("light brown sugar"), Some(0.72));
+        assert_eq!(density_of("sugar snap peas"), None);
+        assert_eq!(density_of("rice vinegar"), None);
+        assert_eq!(density_of("basmati rice"), Some(0.85));
+        assert_eq!(density_of("saltine crackers"), None);
+        assert_eq!(density_of("eggs"), None);
+    }
+
+    #[test]
+    fn print_uk_volume() {
+        let quart = Volume::new_metric(Volume::<Metric>::QUART);
+        let pint = Volume::new_metric(Volume::<Metric>::UK_PINT);
+
+        assert_eq!(quart.as_imperial().to_string(), "1.0 quart");
+        assert_eq!(quart.as_imperial_uk().to_string(), "1.7 pints");
+        assert_eq!(pint.as_imperial_uk().to_string(), "1.0 pint");
+        assert_eq!(
+            Volume::new_metric(Volume::<Metric>::UK_QUART * 2)
+                .as_imperial_uk()
+                .to_string(),
+            "2.0 quarts"
+        );
+        assert_eq!(
+            Volume::new_metric(Volume::<Metric>::TSP)
+                .as_imperial_uk()
+                .to_string(),
+            "1 tsp"
+        );
+        assert_eq!(
+            Weight::new_metric(100_000).as_imperial_uk().to_string(),
+            "3.5 oz"
+        );
+    }
+
+    #[test]
+    fn cycle_unit_system() {
+        let unit = UnitSystem::default();
+
+        assert_eq!(unit, UnitSystem::Metric);
+        assert_eq!(unit.next(), UnitSystem::Imperial);
+        assert_eq!(unit.next().next(), UnitSystem::ImperialUk);
+        assert_eq!(unit.next().next().next(), UnitSystem::Metric);
+
+        for unit in [
+            UnitSystem::Metric,
+            UnitSystem::Imperial,
+            UnitSystem::ImperialUk,
+        ] {
+            assert_eq!(unit.to_string().parse::<UnitSystem>().unwrap(), unit);
+        }
+    }
+
+    #[test]
+    fn parse_pinch() {
+        let pinch = "1 pinch".parse::<Volume>().unwrap();
+
+        assert_eq!(pinch.get(), Volume::<Metric>::PINCH);
+        assert_eq!("a pinch".parse::<Volume>().unwrap(), pinch);
+        assert_eq!(
+            "2 pinches".parse::<Volume>().unwrap().get(),
+            2 * Volume::<Metric>::PINCH
+        );
+        assert_eq!(
+            "2 dashes".parse::<Volume>().unwrap().get(),
+            2 * Volume::<Metric>::DASH
+        );
+        assert_eq!(
+            "an smidgen".parse::<Volume>().unwrap().get(),
+            Volume::<Metric>::SMIDGEN
+        );
+        assert!("a cheese".parse::<Volume>().is_err());
+    }
+
+    #[test]
+    fn print_pinch() {
+        let imperial = |v| Volume::new_imperial(v).to_string();
+
+        assert_eq!(imperial(0), "0 tsp");
+        assert_eq!(imperial(1), "1 smidgen");
+        assert_eq!(imperial(Volume::<Imperial>::SMIDGEN), "1 smidgen");
+        assert_eq!(imperial(Volume::<Imperial>::PINCH), "1 pinch");
+        assert_eq!(imperial(Volume::<Imperial>::DASH), "1/8 tsp");
+    }
+
+    #[test]
+    fn print_tiny_amounts() {
+        assert_eq!(Weight::new_imperial(0).to_string(), "0 oz");
+        assert_eq!(Weight::new_imperial(1).to_string(), "1 mg");
+        assert_eq!(Weight::new_imperial(249).to_string(), "249 mg");
+        assert_eq!(Weight::new_imperial(250).to_string(), "1/8 tsp");
+
+        assert_eq!(Volume::new_metric(0).to_string(), "0 ml");
+        assert_eq!(Volume::new_metric(1).to_string(), "0.001 ml");
+        assert_eq!(Volume::new_metric(30).to_string(), "0.03 ml");
+        assert_eq!(Volume::new_metric(308).to_string(), "0.3 ml");
+        assert_eq!(Volume::new_metric(499).to_string(), "0.5 ml");
+        assert_eq!(Volume::new_metric(616).to_string(), "0.6 ml");
+        assert_eq!(Volume::new_metric(999).to_string(), "1.0 ml");
+
+        assert_eq!(Volume::new_imperial(1).to_string(), "1 smidgen");
+        assert_eq!(
+            Volume::new_imperial(Volume::<Imperial>::PINCH + 1).to_string(),
+            "1 pinch"
+        );
+    }
+
+    #[test]
+    fn snap_to_spoon_midpoints() {
+        let spoons = Volume::<Imperial>::SPOONS;
+
+        for pair in spoons.windows(2) {
+            let [(low, low_name), (high, high_name)] = [pair[0], pair[1]];
+            let midpoint = (low + high) / 2;
+
+            assert_eq!(Volume::new_imperial(low).to_string(), low_name);
+            assert_eq!(Volume::new_imperial(midpoint).to_string(), low_name);
+            assert_eq!(Volume::new_imperial(midpoint + 1).to_string(), high_name);
+            assert_eq!(Volume::new_imperial(high).to_string(), high_name);
+        }
+
+        let limit = Volume::<Imperial>::SPOON_LIMIT;
+        assert_eq!(Volume::new_imperial(limit - 1).to_string(), "1 tbsp");
+        assert_eq!(Volume::new_imperial(limit).to_string(), "0.7 floz");
+        assert_eq!(
+            Volume::<ImperialUk>::from_raw(limit - 1).to_string(),
+            "1 tbsp"
+        );
+    }
+
+    #[test]
+    fn parse_fractions() {
+        assert_eq!(parse_amount("1/2").unwrap(), 0.5);
+        assert_eq!(parse_amount("1 1/2").unwrap(), 1.5);
+        assert_eq!(parse_amount("2.25").unwrap(), 2.25);
+        assert!(parse_amount("1/0").is_err());
+        assert!(parse_amount("1/").is_err());
+        assert!(parse_amount("a 1/2").is_err());
+
+        assert_eq!(
+            "1/2 cup".parse::<Volume>().unwrap(),
+            Volume::new_imperial(Volume::<Imperial>::CUP / 2).as_metric()
+        );
+        assert_eq!(
+            "3/4 tsp".parse::<Volume>().unwrap(),
+            Volume::new_imperial(Volume::<Imperial>::TSP * 3 / 4).as_metric()
+        );
+        assert_eq!(
+            "1 1/2 cups".parse::<Volume>().unwrap(),
+            Volume::new_imperial(Volume::<Imperial>::CUP * 3 / 2).as_metric()
+        );
+
+        assert_eq!(
+            "1/2 kg".parse::<Weight>().unwrap(),
+            Weight::new_metric(500_000)
+        );
+        assert_eq!(
+            "1 1/2 lb".parse::<Weight>().unwrap(),
+            Weight::new_imperial(Weight::<Imperial>::POUND * 3 / 2).as_metric()
+        );
+        assert_eq!(
+            "1 1/2 g sugar".parse::<Weight>().unwrap(),
+            Weight::new_metric(1_500)
+        );
+    }
+
+    #[test]
+    fn duration_clock() {
+        assert_eq!(Duration::from_minutes(20).to_clock(), "20:00");
+        assert_eq!(Duration::from_secs(65).to_clock(), "1:05");
+        assert_eq!(Duration::from_minutes(90).to_clock(), "1:30:00");
+        assert_eq!(Duration::from_secs(0).to_clock(), "0:00");
+    }
+
+    #[test]
+    fn parse_rice_cups() {
+        let rice_cup = Volume::new_metric(Volume::<Metric>::RICE_CUP);
+
+        assert_eq!("1 rice cup".parse::<Volume>().unwrap(), rice_cup);
+        assert_eq!("2 rice cups".parse::<Volume>().unwrap(), rice_cup * 2.0);
+        assert_eq!(
+            "2 Rice Cups of rice".parse::<Volume>().unwrap(),
+            rice_cup * 2.0
+        );
+        assert!("1 rice crumbs".parse::<Volume>().is_err());
+        assert!("1 rice".parse::<Volume>().is_err());
+        assert!("200 g rice".parse::<Volume>().is_err());
+        assert_eq!(
+            "200 g rice".parse::<Weight>().unwrap(),
+            Weight::new_metric(200_000)
+        );
+        assert_eq!(
+            split_unit("rice cups day old rice"),
+            ("rice cups", "day old rice")
+        );
+        assert_eq!(split_unit("cups rice"), ("cups", "rice"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_float_error() {
+        assert!(matches!(
+            "abc g".parse::<Weight>(),
+            Err(MeasurementError::ParseFloat(_))
+        ));
+        assert!(matches!(
+            "abc ml".parse::<Volume>(),
+            Err(MeasurementError::ParseFloat(_))
+        ));
+
+        let e = "abc g".parse::<Weight>().unwrap_err();
+        let json = serde_json::to_string(&e).unwrap();
+        assert_eq!(json, r#"{"ParseFloat":"invalid float literal"}"#);
+        assert!(matches!(
+            serde_json::from_str::<MeasurementError>(&json).unwrap(),
+            MeasurementError::ParseFloat(e) if e.to_string() == "invalid float literal"
+        ));
+    }
+
+    #[test]
+    fn parse_negative_and_zero() {
+        assert!(matches!(
+            "-5 g".parse::<Weight>(),
+            Err(MeasurementError::NegativeAmount)
+        ));
+        assert!(matches!(
+            "-1/2 cup".parse::<Volume>(),
+            Err(MeasurementError::NegativeAmount)
+        ));
+        assert!(matches!(
+            "inf g".parse::<Weight>(),
+            Err(MeasurementError::InvalidFormat)
+        ));
+
+        let zero = "0 ml".parse::<Volume>().unwrap();
+        assert!(zero.is_zero());
+
+        assert_eq!(
+            "1000000 kg".parse::<Weight>().unwrap(),
+            Weight::new_metric(1_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn raw_values() {
+        let weight = Weight::new_metric(453_592);
+        let volume = Volume::new_metric(236_588);
+        let temperature = "180 C".parse::<Temperature>().unwrap();
+
+        assert_eq!(weight.as_imperial().raw(), weight.raw());
+        assert_eq!(weight.as_imperial_uk().raw(), 453_592);
+        assert_eq!(volume.as_imperial().raw(), volume.get());
+        assert_eq!(temperature.as_imperial().raw(), temperature.raw());
+
+        assert_eq!(Weight::<Imperial>::from_raw(453_592), weight.as_imperial());
+        assert_eq!(Volume::<Metric>::from_raw(236_588), volume);
+        assert_eq!(Temperature::<Metric>::from_raw(453_150).celsius(), 180.0);
+    }
+
+    fn metric_raw<Q: Quantity>(q: Q) -> u64 {
+        q.as_metric().raw()
+    }
+
+    #[test]
+    fn quantity_trait() {
+        let weight = Weight::new_imperial(Weight::<Imperial>::POUND);
+        let volume = "1 1/2 cups".parse::<Volume>().unwrap().as_imperial_uk();
+
+        assert_eq!(metric_raw(weight), weight.as_metric().get());
+        assert_eq!(metric_raw(volume), volume.as_metric().get());
+        assert_eq!(Quantity::kind(&weight), QuantityKind::Weight);
+        assert_eq!(Quantity::kind(&volume), QuantityKind::Volume);
+
+        assert_eq!(Quantity::scale(weight, 2.5), weight * 2.5);
+        assert_eq!(Quantity::scale(volume, 0.3), volume * 0.3);
+        assert_eq!(
+            <Weight<ImperialUk> as Quantity>::from_raw(1_000),
+            Weight::<ImperialUk>::from_raw(1_000)
+        );
+        assert_eq!(
+            Quantity::as_imperial(volume).to_string(),
+            volume.as_imperial().to_string()
+        );
+    }
+
+    #[test]
+    fn print_precise_grams() {
+        let grams = Weight::<Metric>::from_raw;
+
+        assert_eq!(grams(1250).to_string(), "1 g");
+        assert_eq!(grams(1250).precise().to_string(), "1.2 g");
+        assert_eq!(format!("{:#}", grams(1250)), "1.2 g");
+        assert_eq!(grams(750).precise().to_string(), "750 mg");
+        assert_eq!(grams(12_600).precise().to_string(), "13 g");
+        assert_eq!(grams(1_250_000).to_string(), "1.2 kg");
+        assert_eq!(grams(1_250_000).precise().to_string(), "1250 g");
+        assert_eq!(grams(0).precise().to_string(), "0 g");
+    }
+
+    #[test]
+    fn get_in_any_unit_system() {
+        let pound = Weight::new_imperial(Weight::<Imperial>::POUND);
+        let cup = Volume::new_imperial(Volume::<Imperial>::CUP).as_imperial_uk();
+
+        assert_eq!(pound.get(), pound.as_metric().get());
+        assert_eq!(cup.get(), Volume::<Imperial>::CUP);
+        assert_eq!(
+            "200 C".parse::<Temperature>().unwrap().as_imperial().get(),
+            473_150
+        );
+    }
+
+    #[test]
+    fn duration_iso8601() {
+        assert_eq!(Duration::from_minutes(20).to_iso8601(), "PT20M");
+        assert_eq!(Duration::from_minutes(90).to_iso8601(), "PT1H30M");
+        assert_eq!(Duration::from_secs(3605).to_iso8601(), "PT1H5S");
+        assert_eq!(Duration::from_secs(0).to_iso8601(), "PT0S");
+
+        assert_eq!("PT90M".parse::<Duration>(), Ok(Duration::from_minutes(90)));
+        assert_eq!(
+            "PT1H30M".parse::<Duration>(),
+            Ok(Duration::from_minutes(90))
+        );
+        assert_eq!("pt45s".parse::<Duration>(), Ok(Duration::from_secs(45)));
+        assert_eq!(
+            "P1DT2H".parse::<Duration>(),
+            Ok(Duration::from_minutes(26 * 60))
+        );
+        assert_eq!(
+            "P2D".parse::<Duration>(),
+            Ok(Duration::from_minutes(48 * 60))
+        );
+        assert_eq!(
+            Duration::from_iso8601("PT0.5H"),
+            Ok(Duration::from_minutes(30))
+        );
+
+        for invalid in ["P", "PT", "P1H", "PT1 hour", "P1W", "1h 30m"] {
+            assert!(Duration::from_iso8601(invalid).is_err(), "{invalid}");
+        }
+
+        for human in ["1h 30m", "PT90M"] {
+            let duration = human.parse::<Duration>().unwrap();
+
+            assert_eq!(duration.to_iso8601().parse::<Duration>(), Ok(duration));
+            assert_eq!(duration.to_string().parse::<Duration>(), Ok(duration));
+            assert_eq!(duration.to_string(), "1h 30m");
+        }
+    }
+
+    #[test]
+    fn unit_system_uses() {
+        assert!(UnitSystem::Imperial.uses("8 oz"));
+        assert!(UnitSystem::ImperialUk.uses("1 lb"));
+        assert!(!UnitSystem::Metric.uses("8 oz"));
+        assert!(UnitSystem::Metric.uses("80 mL"));
+        assert!(!UnitSystem::Imperial.uses("1.5 kg"));
+        assert!(UnitSystem::Imperial.uses("1 1/2 cups"));
+        assert!(!UnitSystem::ImperialUk.uses("1-2 cups"));
+        assert!(UnitSystem::Metric.uses("2 tsp"));
+        assert!(UnitSystem::Metric.uses("5 rice cups"));
+        assert!(UnitSystem::Metric.uses("2 to 3"));
+    }
+
+    #[test]
+    fn try_from_str() {
+        for s in ["200 g", "1.5 kg", "8 oz", "1 lb", "", "200", "2 cups"] {
+            assert_eq!(Weight::try_from(s), s.parse::<Weight>());
+            assert_eq!(Weight::try_from(s.to_string()), s.parse::<Weight>());
+        }
+
+        for s in [
+            "250 ml",
+            "1 1/2 cups",
+            "a pinch",
+            "2 tbsp",
+            "",
+            "1/0 tsp",
+            "200 g",
+        ] {
+            assert_eq!(Volume::try_from(s), s.parse::<Volume>());
+            assert_eq!(Volume::try_from(s.to_string()), s.parse::<Volume>());
+        }
+    }
+
+    #[test]
+    fn fractional_cups() {
+        let cups = |ml: u64| Volume::new_imperial(ml * 1000).to_string();
+
+        assert_eq!(cups(59), "1/4 cup");
+        assert_eq!(cups(79), "1/3 cup");
+        assert_eq!(cups(118), "1/2 cup");
+        assert_eq!(cups(120), "1/2 cup");
+        assert_eq!(cups(158), "2/3 cup");
+        assert_eq!(cups(177), "3/4 cup");
+        assert_eq!(cups(237), "1 cup");
+        assert_eq!(cups(315), "1 1/3 cups");
+        assert_eq!(cups(355), "1 1/2 cups");
+        assert_eq!(cups(710), "3 cups");
+        assert_eq!(cups(887), "3 3/4 cups");
+
+        // Too far from any measuring cup
+        assert_eq!(cups(100), "3.4 floz");
+        assert_eq!(cups(290), "1.2 cups");
+        // Parsed fractions land back on themselves
+        for s in ["1/3 cup", "2/3 cup", "2 1/4 cups"] {
+            assert_eq!(s.parse::<Volume>().unwrap().as_imperial().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn eq_value_across_unit_systems() {
+        let pound = "1 lb".parse::<Weight>().unwrap();
+
+        assert!(pound.eq_value(&Weight::new_imperial(Weight::<Imperial>::POUND)));
+        assert!(pound.eq_value(&pound.as_imperial_uk()));
+        assert!(!pound.eq_value(&Weight::new_imperial(Weight::<Imperial>::OUNCE)));
+
+        let cup = "1 cup".parse::<Volume>().unwrap();
+
+        assert!(cup.eq_value(&Volume::new_imperial(Volume::<Imperial>::CUP)));
+        assert!(!cup.eq_value(&Volume::<ImperialUk>::from_raw(
+            Volume::<ImperialUk>::UK_CUP
+        )));
+    }
+
+    #[test]
+    fn large_amounts_keep_a_decimal() {
+        assert_eq!(Volume::new_metric(7_900_000).to_string(), "7.9 l");
+        assert_eq!(Volume::new_metric(5_000_000).to_string(), "5 l");
+        assert_eq!(Volume::new_metric(12_960_000).to_string(), "13 l");
+        assert_eq!(Weight::new_metric(12_500_000).to_string(), "12.5 kg");
+        assert_eq!(Weight::new_metric(10_000_000).to_string(), "10 kg");
+
+        let pound = Weight::<Imperial>::POUND;
+        assert_eq!(Weight::new_imperial(pound * 45 / 10).to_string(), "4.5 lb");
+        let quart = Volume::<Imperial>::QUART;
+        assert_eq!(
+            Volume::new_imperial(quart * 55 / 10).to_string(),
+            "5.5 quarts"
+        );
+        let uk_quart = Volume::<ImperialUk>::UK_QUART;
+        assert_eq!(
+            Volume::<ImperialUk>::from_raw(uk_quart * 65 / 10).to_string(),
+            "6.5 quarts"
+        );
+    }
+
+    #[test]
+    fn decimal_commas() {
+        assert_eq!(parse_amount("1,5").unwrap(), 1.5);
+        assert_eq!(
+            "1,5 kg".parse::<Weight>().unwrap(),
+            "1.5 kg".parse().unwrap()
+        );
+        assert_eq!(
+            "0,25 l".parse::<Volume>().unwrap(),
+            Volume::new_metric(250_000)
+        );
+        assert!(parse_amount("1,").is_err());
+        assert!(parse_amount(",5").is_err());
+        assert_eq!(parse_amount("1,000").unwrap(), 1000.0);
+        assert_eq!(parse_amount("12,345,678").unwrap(), 12_345_678.0);
+        assert_eq!(
+            "1,000 g".parse::<Weight>().unwrap(),
+            Weight::new_metric(1_000_000)
+        );
+        assert!(parse_amount("1,5000").is_err());
+        assert!(parse_amount("1000,000").is_err());
+        assert!(parse_amount("1,00,000").is_err());
+
+        let comma = DecimalStyle::Comma;
+        assert_eq!(
+            comma.display(Weight::new_metric(1_500_000)).to_string(),
+            "1,5 kg"
+        );
+        assert_eq!(comma.display(Volume::new_metric(250)).to_string(), "0,2 ml");
+        assert_eq!(
+            comma.display("1 1/2 cups, 2.").to_string(),
+            "1 1/2 cups, 2."
+        );
+        assert_eq!(
+            DecimalStyle::Point
+                .display(Weight::new_metric(1_500_000))
+                .to_string(),
+            "1.5 kg"
+        );
+    }
+
+    #[test]
+    fn normalize_display_units() {
+        let weight = |mg| Weight::new_metric(mg).normalize_display().unit;
+        let volume = |ul| Volume::new_metric(ul).normalize_display().unit;
+
+        assert_eq!(weight(0), "g");
+        assert_eq!(weight(750), "mg");
+        assert_eq!(weight(250_000), "g");
+        assert_eq!(weight(1_500_000), "kg");
+        assert_eq!(weight(12_500_000), "kg");
+
+        assert_eq!(volume(0), "ml");
+        assert_eq!(volume(250), "ml");
+        assert_eq!(volume(250_000), "ml");
+        assert_eq!(volume(750_000), "l");
+        assert_eq!(volume(7_900_000), "l");
+
+        assert_eq!(
+            Volume::new_metric(250_000).normalize_display(),
+            NormalizedAmount {
+                amount: 250.,
+                unit: "ml",
+                decimals: Some(0)
+            }
+        );
+        assert_eq!(
+            Weight::new_metric(1_500_000).normalize_display().amount,
+            1.5
+        );
+
+        let grams = |mg| Weight::new_metric(mg).normalize_display().amount;
+        let millilitres = |ul| Volume::new_metric(ul).normalize_display().amount;
+
+        assert_eq!(grams(1_999), 2.);
+        assert_eq!(grams(2_400), 2.);
+        assert_eq!(grams(999_400), 999.);
+        assert_eq!(weight(999_600), "kg");
+        assert_eq!(Weight::new_metric(999_600).to_string(), "1.0 kg");
+        assert_eq!(millilitres(18_480), 18.);
+        assert_eq!(millilitres(19_700), 20.);
+        assert_eq!(millilitres(499_400), 499.);
+        assert_eq!(volume(499_600), "l");
+        assert_eq!(Volume::new_metric(499_600).to_string(), "0.5 l");
+    }
+
+    #[test]
+    fn pluralized_unit_words() {
+        let imperial = |ul| Volume::new_imperial(ul).to_string();
+
+        assert_eq!(imperial(Volume::<Metric>::CUP), "1 cup");
+        assert_eq!(imperial(Volume::<Metric>::CUP * 2), "2 cups");
+        // Too far from a whole cup for a measuring cup, still reads as one
+        assert_eq!(imperial(Volume::<Metric>::CUP * 103 / 100), "1.0 cup");
+        assert_eq!(imperial(Volume::<Metric>::CUP * 11 / 10), "1.1 cups");
+
+        assert_eq!(imperial(Volume::<Metric>::QUART), "1.0 quart");
+        assert_eq!(imperial(Volume::<Metric>::QUART * 3), "3.0 quarts");
+        assert_eq!(imperial(Volume::<Metric>::QUART * 5), "5 quarts");
+    }
 }
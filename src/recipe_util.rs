@@ -1,5 +1,9 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -13,87 +17,481 @@ use crate::measurements::*;
 //     },
 // };
 
-#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Error)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RecipeError {
     #[error("Expected a title for the recipe")]
     ExpectedTitle,
     #[error("Encountered `image:` but no subsequent href was provided")]
     ExpectedImageHref,
+    #[error("Expected a number of servings after `{0}:`")]
+    ExpectedServings(String),
+    #[error("Invalid duration after `{0}:`: {1}")]
+    InvalidDuration(String, MeasurementError),
+    #[error("Expected a `---` delimited YAML block at the start of the recipe")]
+    ExpectedFrontMatter,
     #[error("Expected `---ingredients` to indicate the start of the ingredient list")]
     ExpectedIngredientsStart,
     #[error("Expected an ingredient, found an empty string")]
     ExpectedIngredient,
+    #[error("Unknown difficulty `{0}`, expected easy, medium or hard")]
+    InvalidDifficulty(String),
+    #[error("Expected a rating from 0 to 5, found `{0}`")]
+    InvalidRating(String),
+    #[error("Expected a number for `{key}:`, found `{value}`")]
+    InvalidNutrition { key: String, value: String },
+    #[error("Unknown nutrient `{0}`")]
+    UnknownNutrient(String),
     #[error("Expected `---steps` to indicate the start of the recipe steps")]
     ExpectedStepsStart,
     #[error("Expected {0}, found EOF")]
     UnexpectedEOF(String),
     #[error("{0}")]
     CustomString(String),
+    #[error("Couldn't load the recipe: {0}")]
+    Network(String),
+    #[error("There's no recipe at `{0}`")]
+    NotFound(String),
+    #[error("Line {line}: {source}")]
+    AtLine {
+        line: usize,
+        source: Box<RecipeError>,
+    },
+}
+
+impl RecipeError {
+    /// The underlying error, without any line information.
+    pub fn inner(&self) -> &RecipeError {
+        match self {
+            RecipeError::AtLine { source, .. } => source.inner(),
+            e => e,
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            RecipeError::AtLine { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+}
+
+/// Lint-level feedback on a recipe that parsed fine but probably has a mistake
+#[derive(Debug, Clone, PartialEq, Error)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecipeWarning {
+    #[error("The recipe has no ingredients")]
+    NoIngredients,
+    #[error("The recipe has no steps")]
+    NoSteps,
+    #[error("`{0}` isn't mentioned in any step")]
+    UnusedIngredient(String),
+    #[error("Step {step} mentions `{ingredient}`, which isn't in the ingredient list")]
+    UnlistedIngredient { step: usize, ingredient: String },
+    #[error("The image href `{0}` doesn't look like a URL or path")]
+    InvalidImageHref(String),
+    #[error("`{0}` has a quantity of zero")]
+    ZeroQuantity(String),
 }
 
 #[test]
 fn fromstr() {
     let base = "8 eggs";
-    let mass = Volume::from_str(base);
-    println!("{mass:?}");
-    println!("{:?}", base.split_once(' ').unwrap().0.parse::<u64>());
+    assert!(Volume::from_str(base).is_err());
+    assert_eq!(base.split_once(' ').unwrap().0.parse::<u64>(), Ok(8));
 }
 
-#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IngredientQuantity<T = Metric> {
     Weight(Weight<T>),
     Volume(Volume<T>),
+    Count(f64),
+    /// A low and a high bound of the same kind, like `2-3 cups`
+    Range(Box<IngredientQuantity<T>>, Box<IngredientQuantity<T>>),
 }
 
 impl<T> IngredientQuantity<T> {
-    pub const fn as_imperial(self) -> IngredientQuantity<Imperial> {
+    /// Whether the quantity is nothing at all. A range is only zero when its
+    /// upper end is.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Weight(w) => w.is_zero(),
+            Self::Volume(v) => v.is_zero(),
+            Self::Count(c) => *c == 0.0,
+            Self::Range(_, h) => h.is_zero(),
+        }
+    }
+
+    /// Retags the quantity with another unit system, keeping the amount
+    fn convert<U>(self) -> IngredientQuantity<U> {
         match self {
-            Self::Weight(w) => IngredientQuantity::Weight(w.as_imperial()),
-            Self::Volume(v) => IngredientQuantity::Volume(v.as_imperial()),
+            Self::Weight(w) => IngredientQuantity::Weight(Weight::from_raw(w.raw())),
+            Self::Volume(v) => IngredientQuantity::Volume(Volume::from_raw(v.raw())),
+            Self::Count(c) => IngredientQuantity::Count(c),
+            Self::Range(l, h) => {
+                IngredientQuantity::Range(Box::new(l.convert()), Box::new(h.convert()))
+            }
         }
     }
 
-    pub const fn as_metric(self) -> IngredientQuantity<Metric> {
+    /// The same quantity, shown in imperial units
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// let quantity = recipe_book::IngredientQuantity::Count(2.);
+    /// quantity.as_imperial();
+    /// ```
+    #[must_use]
+    pub fn as_imperial(self) -> IngredientQuantity<Imperial> {
+        self.convert()
+    }
+
+    #[must_use]
+    pub fn as_metric(self) -> IngredientQuantity<Metric> {
+        self.convert()
+    }
+
+    #[must_use]
+    pub fn as_imperial_uk(self) -> IngredientQuantity<ImperialUk> {
+        self.convert()
+    }
+
+    /// What the quantity measures, or `None` for counts. A range has the kind
+    /// of its ends.
+    #[must_use]
+    pub fn kind(&self) -> Option<QuantityKind> {
         match self {
-            Self::Weight(w) => IngredientQuantity::Weight(w.as_metric()),
-            Self::Volume(v) => IngredientQuantity::Volume(v.as_metric()),
+            Self::Weight(_) => Some(QuantityKind::Weight),
+            Self::Volume(_) => Some(QuantityKind::Volume),
+            Self::Count(_) => None,
+            Self::Range(l, _) => l.kind(),
         }
     }
 }
 
-impl std::fmt::Display for IngredientQuantity<Metric> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Copy> IngredientQuantity<T> {
+    #[must_use]
+    pub fn scale(&self, factor: f64) -> Self {
         match self {
-            IngredientQuantity::Weight(w) => write!(f, "{w}"),
-            IngredientQuantity::Volume(v) => write!(f, "{v}"),
+            Self::Weight(w) => Self::Weight(Quantity::scale(*w, factor)),
+            Self::Volume(v) => Self::Volume(Quantity::scale(*v, factor)),
+            Self::Count(c) => Self::Count(c * factor),
+            Self::Range(l, h) => Self::Range(Box::new(l.scale(factor)), Box::new(h.scale(factor))),
+        }
+    }
+
+    /// Snaps the quantity to something that can be measured out in `unit`, see
+    /// [`Rounding`]
+    #[must_use]
+    pub fn round(&self, rounding: Rounding, unit: UnitSystem) -> Self {
+        match (self, rounding) {
+            (_, Rounding::Exact) => self.clone(),
+            (Self::Weight(w), _) => Self::Weight(Weight::from_raw(round_weight(w.raw()))),
+            (Self::Volume(v), _) => Self::Volume(Volume::from_raw(match unit {
+                UnitSystem::Metric => round_metric_volume(v.raw()),
+                UnitSystem::Imperial | UnitSystem::ImperialUk => round_volume(v.raw()),
+            })),
+            (Self::Count(c), Rounding::FriendlyFractions) => Self::Count(round_count(*c, 2.0)),
+            (Self::Count(c), Rounding::NearestWhole) => Self::Count(round_count(*c, 1.0)),
+            (Self::Range(l, h), _) => Self::Range(
+                Box::new(l.round(rounding, unit)),
+                Box::new(h.round(rounding, unit)),
+            ),
+        }
+    }
+}
+
+/// How quantities are rounded after scaling a recipe
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rounding {
+    /// Keep the scaled amounts as they are, `1.5 eggs` and all
+    #[default]
+    Exact,
+    /// Counts go to the nearest half, or a whole number for things that only
+    /// come whole like eggs. Volumes go to a quarter of the spoon or cup they'd
+    /// be measured with, or a round number of millilitres in metric, and weights
+    /// to a whole number of grams.
+    FriendlyFractions,
+    /// Like [`Rounding::FriendlyFractions`], but counts are whole
+    NearestWhole,
+}
+
+/// Things that only come whole, which [`Rounding::FriendlyFractions`] doesn't
+/// halve, matched against the words of an ingredient's canonical name
+const WHOLE_ITEMS: &[&str] = &[
+    "egg", "yolk", "tortilla", "sheet", "leaf", "clove", "can", "tin", "sachet", "packet", "cube",
+];
+
+/// Rounds to the nearest `1 / per`, never rounding something down to nothing
+fn round_count(count: f64, per: f64) -> f64 {
+    match (count * per).round() / per {
+        0.0 if count > 0.0 => 1.0 / per,
+        rounded => rounded,
+    }
+}
+
+/// Whole grams below 100 g, 5 g steps up to 1 kg and 10 g steps past that
+fn round_weight(mg: u64) -> u64 {
+    let step = match mg {
+        0..100_000 => 1_000.0,
+        100_000..1_000_000 => 5_000.0,
+        _ => 10_000.0,
+    };
+
+    round_to_step(mg, step)
+}
+
+/// Whole millilitres below 10 ml, then 5 ml steps up to 100 ml, 25 ml steps up
+/// to a litre and 100 ml steps past that
+fn round_metric_volume(ul: u64) -> u64 {
+    let step = match ul {
+        0..10_000 => 1_000.0,
+        10_000..100_000 => 5_000.0,
+        100_000..1_000_000 => 25_000.0,
+        _ => 100_000.0,
+    };
+
+    round_to_step(ul, step)
+}
+
+/// A quarter of the largest of tsp, tbsp or cup that fits, or a dash below a tsp
+fn round_volume(ul: u64) -> u64 {
+    const TSP: u64 = Volume::<Metric>::TSP;
+    const TBSP: u64 = Volume::<Metric>::TBSP;
+    const CUP: u64 = Volume::<Metric>::CUP;
+
+    let step = match ul {
+        0..TSP => TSP as f64 / 8.0,
+        TSP..TBSP => TSP as f64 / 4.0,
+        TBSP..CUP => TBSP as f64 / 4.0,
+        _ => CUP as f64 / 4.0,
+    };
+
+    round_to_step(ul, step)
+}
+
+fn round_to_step(amount: u64, step: f64) -> u64 {
+    let steps = match (amount as f64 / step).round() {
+        0.0 if amount > 0 => 1.0,
+        steps => steps,
+    };
+
+    (steps * step).round() as u64
+}
+
+/// Takes the weight out of a quantity, handing the quantity back when it's a
+/// volume, count or range instead
+impl<T> TryFrom<IngredientQuantity<T>> for Weight<T> {
+    type Error = IngredientQuantity<T>;
+
+    fn try_from(quantity: IngredientQuantity<T>) -> Result<Self, Self::Error> {
+        match quantity {
+            IngredientQuantity::Weight(w) => Ok(w),
+            other => Err(other),
+        }
+    }
+}
+
+/// Takes the volume out of a quantity, handing the quantity back when it's a
+/// weight, count or range instead
+impl<T> TryFrom<IngredientQuantity<T>> for Volume<T> {
+    type Error = IngredientQuantity<T>;
+
+    fn try_from(quantity: IngredientQuantity<T>) -> Result<Self, Self::Error> {
+        match quantity {
+            IngredientQuantity::Volume(v) => Ok(v),
+            other => Err(other),
+        }
+    }
+}
+
+/// The low end of a range is written in the unit the high end is displayed in,
+/// so `2-3 cups` in metric reads `0.5-0.7 l` rather than `473 ml-0.7 l`. It keeps
+/// its own unit when there's no amount to write in the high end's unit, like a
+/// pinch next to cups, and then both units are written (`1 pinch-1/4 tsp`).
+fn write_range(f: &mut std::fmt::Formatter<'_>, low: &str, high: &str) -> std::fmt::Result {
+    match (low.split_once(' '), high.split_once(' ')) {
+        (Some((l, lu)), Some((h, hu))) if lu == hu => write!(f, "{l}-{h} {hu}"),
+        _ => write!(f, "{low}-{high}"),
+    }
+}
+
+/// The measuring fractions amounts are written with when the unit they're in
+/// is written with one, like `1/2 tbsp`
+const MEASURING_FRACTIONS: [(f64, &str); 8] = [
+    (0., ""),
+    (1. / 8., "1/8"),
+    (1. / 4., "1/4"),
+    (1. / 3., "1/3"),
+    (1. / 2., "1/2"),
+    (2. / 3., "2/3"),
+    (3. / 4., "3/4"),
+    (1., ""),
+];
+
+/// Writes `amount` as precisely as `like` is written: as a measuring fraction
+/// if it's one, with as many decimals as it has or as a whole number. `None`
+/// when the amount would be written as zero.
+fn amount_like(amount: f64, like: &str) -> Option<String> {
+    if like.contains('/') {
+        let whole = amount.floor();
+        let (fraction, label) = MEASURING_FRACTIONS
+            .iter()
+            .min_by(|(a, _), (b, _)| {
+                (a - amount.fract())
+                    .abs()
+                    .total_cmp(&(b - amount.fract()).abs())
+            })
+            .expect("There are measuring fractions");
+
+        return match (whole + fraction.floor(), *label) {
+            (whole, "") if whole == 0. => None,
+            (whole, "") => Some(whole.to_string()),
+            (whole, label) if whole == 0. => Some(label.to_string()),
+            (whole, label) => Some(format!("{whole} {label}")),
+        };
+    }
+
+    let decimals = like.split_once('.').map_or(0, |(_, d)| d.len());
+    let written = format!("{amount:.decimals$}");
+
+    match written.parse::<f64>() {
+        Ok(0.) => None,
+        _ => Some(written),
+    }
+}
+
+impl<T: Copy> IngredientQuantity<T>
+where
+    Weight<T>: DisplayUnits,
+    Volume<T>: DisplayUnits,
+{
+    /// The quantity written in the unit of `shown`, another quantity as it's
+    /// displayed, like `0.5 l` for 473 ml next to `0.7 l`
+    fn in_unit_of(&self, shown: &str) -> Option<String> {
+        let (like, unit) = split_amount(shown)?;
+        let (raw, size) = match self {
+            Self::Weight(w) => (w.raw(), Weight::<T>::unit_size(unit)?),
+            Self::Volume(v) => (v.raw(), Volume::<T>::unit_size(unit)?),
+            _ => return None,
+        };
+
+        Some(format!(
+            "{} {unit}",
+            amount_like(raw as f64 / size as f64, like)?
+        ))
+    }
+}
+
+impl<T: Copy> IngredientQuantity<T> {
+    /// Adds two quantities of the same kind, or `None` if they can't be combined.
+    /// Ranges never combine.
+    #[must_use]
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Weight(a), Self::Weight(b)) => Some(Self::Weight(*a + *b)),
+            (Self::Volume(a), Self::Volume(b)) => Some(Self::Volume(*a + *b)),
+            (Self::Count(a), Self::Count(b)) => Some(Self::Count(a + b)),
+            _ => None,
         }
     }
 }
 
-impl std::fmt::Display for IngredientQuantity<Imperial> {
+impl<T: Copy> std::fmt::Display for IngredientQuantity<T>
+where
+    Weight<T>: std::fmt::Display + DisplayUnits,
+    Volume<T>: std::fmt::Display + DisplayUnits,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            IngredientQuantity::Weight(w) if f.alternate() => write!(f, "{w:#}"),
             IngredientQuantity::Weight(w) => write!(f, "{w}"),
             IngredientQuantity::Volume(v) => write!(f, "{v}"),
+            IngredientQuantity::Count(c) => write!(f, "{c}"),
+            IngredientQuantity::Range(l, h) => {
+                let (low, high) = match f.alternate() {
+                    true => (format!("{l:#}"), format!("{h:#}")),
+                    false => (l.to_string(), h.to_string()),
+                };
+                let low = l.in_unit_of(&high).unwrap_or(low);
+
+                write_range(f, &low, &high)
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ingredient<T = Metric> {
     pub ingredient: String,
+    /// How to prepare it, written after the first comma, like `minced`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub prep: Option<String>,
     pub quantity: Option<IngredientQuantity<T>>,
+    /// The quantity as the author wrote it, like `8 oz`, shown instead of the
+    /// converted quantity when it's already in the unit system being displayed.
+    /// Dropped once the quantity changes, like when scaling.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub original: Option<String>,
+    /// How much to use when there's no quantity, like `to taste`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub qualifier: Option<String>,
+    /// What can be used instead, written after `or` like `olive oil` in
+    /// `2 tbsp butter or olive oil`. Only has a quantity if it was given its own.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")
+    )]
+    pub alternatives: Vec<Ingredient<T>>,
+    /// Marked with a trailing `(optional)` in the recipe file
+    pub optional: bool,
+    /// Set when the ingredient is made from another recipe, like `[[béchamel]]`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub sub_recipe: Option<SubRecipeRef>,
+    /// Shown in this unit system whatever the reader picked, from a trailing
+    /// annotation like `[imperial]`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub forced_unit: Option<UnitSystem>,
     // unit: PhantomData<U>
 }
 
 impl<T> Ingredient<T> {
+    pub const OPTIONAL_MARKER: &'static str = "(optional)";
+
     pub fn as_imperial(self) -> Ingredient<Imperial> {
         let ingredient = self.ingredient;
         let quantity = self.quantity.map(|q| q.as_imperial());
 
         Ingredient {
             ingredient,
-            quantity
+            prep: self.prep,
+            quantity,
+            original: self.original,
+            qualifier: self.qualifier,
+            alternatives: self
+                .alternatives
+                .into_iter()
+                .map(Ingredient::as_imperial)
+                .collect(),
+            optional: self.optional,
+            sub_recipe: self.sub_recipe,
+            forced_unit: self.forced_unit,
         }
     }
 
@@ -103,215 +501,3412 @@ impl<T> Ingredient<T> {
 
         Ingredient {
             ingredient,
-            quantity
+            prep: self.prep,
+            quantity,
+            original: self.original,
+            qualifier: self.qualifier,
+            alternatives: self
+                .alternatives
+                .into_iter()
+                .map(Ingredient::as_metric)
+                .collect(),
+            optional: self.optional,
+            sub_recipe: self.sub_recipe,
+            forced_unit: self.forced_unit,
+        }
+    }
+
+    pub fn as_imperial_uk(self) -> Ingredient<ImperialUk> {
+        let ingredient = self.ingredient;
+        let quantity = self.quantity.map(|q| q.as_imperial_uk());
+
+        Ingredient {
+            ingredient,
+            prep: self.prep,
+            quantity,
+            original: self.original,
+            qualifier: self.qualifier,
+            alternatives: self
+                .alternatives
+                .into_iter()
+                .map(Ingredient::as_imperial_uk)
+                .collect(),
+            optional: self.optional,
+            sub_recipe: self.sub_recipe,
+            forced_unit: self.forced_unit,
         }
     }
 }
 
-impl FromStr for Ingredient {
-    type Err = RecipeError;
+impl<T> Ingredient<T> {
+    /// The unit system the quantity was written in, or the one it's forced to
+    /// be shown in
+    pub fn written_unit_system(&self) -> Option<UnitSystem> {
+        self.forced_unit
+            .or_else(|| UnitSystem::written_in(self.original.as_deref()?))
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use RecipeError::ExpectedIngredient;
+    /// Whether it's something like an egg, which can't be measured out in
+    /// halves
+    fn only_comes_whole(&self) -> bool {
+        canonical_ingredient(&self.ingredient)
+            .split(' ')
+            .any(|word| WHOLE_ITEMS.contains(&word))
+    }
+}
 
-        if s.is_empty() {
-            return Err(ExpectedIngredient);
+impl<T: Copy> Ingredient<T> {
+    pub fn scale(&self, factor: f64) -> Self {
+        Ingredient {
+            ingredient: self.ingredient.clone(),
+            prep: self.prep.clone(),
+            quantity: self.quantity.as_ref().map(|q| q.scale(factor)),
+            original: self.original.clone().filter(|_| factor == 1.0),
+            qualifier: self.qualifier.clone(),
+            alternatives: self.alternatives.iter().map(|a| a.scale(factor)).collect(),
+            optional: self.optional,
+            sub_recipe: self.sub_recipe.clone(),
+            forced_unit: self.forced_unit,
+        }
+    }
+}
+
+impl IngredientQuantity {
+    /// Splits a leading quantity off an ingredient line, returning it along with
+    /// the rest of the line.
+    fn parse_leading(s: &str) -> (Option<Self>, &str) {
+        if let Some((range, rest)) = Self::parse_leading_range(s) {
+            return (Some(range), rest);
         }
 
-        let amount_i = s
-            .char_indices()
-            .filter(|(_, c)| *c == ' ')
-            .skip(1)
-            .next()
-            .map(|(i, _)| i)
+        let amount_i = split_amount(s)
+            .map(|(_, rest)| s.len() - split_unit(rest).1.len())
             .unwrap_or(s.len());
 
         let amount = &s[..amount_i].trim_end();
 
         if let Ok(m) = Weight::from_str(amount) {
-            let ingredient = s[amount_i..].trim_start().to_string();
-            let quantity = Some(IngredientQuantity::Weight(m));
-
-            return Ok(Self {
-                ingredient,
-                quantity,
-            });
+            return (Some(Self::Weight(m)), s[amount_i..].trim_start());
         }
 
         if let Ok(v) = Volume::from_str(amount) {
-            let ingredient = s[amount_i..].trim_start().to_string();
-            let quantity = Some(IngredientQuantity::Volume(v));
+            return (Some(Self::Volume(v)), s[amount_i..].trim_start());
+        }
 
-            return Ok(Self {
-                ingredient,
-                quantity,
-            });
+        if let Some((count, rest)) = split_amount(s) {
+            if let Ok(c) = parse_amount(count) {
+                return (Some(Self::Count(c)), rest.trim_start());
+            }
         }
 
-        Ok(Self {
-            ingredient: s.to_string(),
-            quantity: None,
+        (None, s)
+    }
+
+    /// Parses ranges like `2-3 cups flour` or `2 to 3 cloves garlic` by parsing
+    /// each end on its own, so both ends share the unit.
+    fn parse_leading_range(s: &str) -> Option<(Self, &str)> {
+        use IngredientQuantity::*;
+
+        let (first, rest) = s.split_once(' ')?;
+
+        let (low, high, rest) = match first.split_once('-') {
+            Some((low, high)) => (low, high, rest),
+            None => {
+                let (high, rest) = rest.strip_prefix("to ")?.split_once(' ')?;
+                (first, high, rest)
+            }
+        };
+
+        parse_amount(low).ok()?;
+        parse_amount(high).ok()?;
+
+        let low = format!("{low} {rest}");
+        let high = format!("{high} {rest}");
+        let (low, _) = Self::parse_leading(&low);
+        let (high, name) = Self::parse_leading(&high);
+        let name = &rest[rest.len() - name.len()..];
+
+        match (low?, high?) {
+            (l @ Weight(_), h @ Weight(_))
+            | (l @ Volume(_), h @ Volume(_))
+            | (l @ Count(_), h @ Count(_)) => Some((Range(Box::new(l), Box::new(h)), name)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Copy> Ingredient<T> {
+    /// Swaps a weight for a volume or the other way around, when the ingredient
+    /// has a known density.
+    pub fn convert_with_density(&self) -> Option<Self> {
+        let density = density_of(&self.ingredient)?;
+        let quantity = match self.quantity.as_ref()? {
+            IngredientQuantity::Weight(w) => IngredientQuantity::Volume(w.to_volume(density)),
+            IngredientQuantity::Volume(v) => IngredientQuantity::Weight(v.to_weight(density)),
+            _ => return None,
+        };
+
+        Some(Ingredient {
+            quantity: Some(quantity),
+            original: None,
+            ..self.clone()
         })
     }
 }
 
-impl std::fmt::Display for Ingredient<Metric> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(q) = &self.quantity {
-            write!(f, "{q} ")?;
+impl<T: Copy> Ingredient<T> {
+    /// Formats the ingredient in the given unit system, or the one it's forced
+    /// to be shown in, keeping the quantity as written when it's already in
+    /// that system
+    pub fn to_string_in(&self, unit: UnitSystem) -> String {
+        self.format_in(unit, false)
+    }
+
+    /// Like [`Ingredient::to_string_in`], but metric weights keep at least gram
+    /// precision, like [`Weight::precise`]
+    pub fn to_precise_string_in(&self, unit: UnitSystem) -> String {
+        self.format_in(unit, true)
+    }
+
+    fn format_in(&self, unit: UnitSystem, precise: bool) -> String {
+        fn format<D: std::fmt::Display>(d: D, precise: bool) -> String {
+            match precise {
+                true => format!("{d:#}"),
+                false => d.to_string(),
+            }
+        }
+
+        let unit = self.forced_unit.unwrap_or(unit);
+        let (text, quantity) = match unit {
+            UnitSystem::Metric => {
+                let i = self.clone().as_metric();
+                (format(&i, precise), i.quantity.map(|q| format(q, precise)))
+            }
+            UnitSystem::Imperial => {
+                let i = self.clone().as_imperial();
+                (format(&i, precise), i.quantity.map(|q| format(q, precise)))
+            }
+            UnitSystem::ImperialUk => {
+                let i = self.clone().as_imperial_uk();
+                (format(&i, precise), i.quantity.map(|q| format(q, precise)))
+            }
+        };
+
+        let original = self.original.as_ref().filter(|o| unit.uses(o));
+
+        match (original, quantity) {
+            (Some(original), Some(quantity)) => format!("{original}{}", &text[quantity.len()..]),
+            _ => text,
         }
-        write!(f, "{}", self.ingredient)
     }
 }
 
-impl std::fmt::Display for Ingredient<Imperial> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(q) = &self.quantity {
-            write!(f, "{q} ")?;
+impl FromStr for Ingredient {
+    type Err = RecipeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use RecipeError::ExpectedIngredient;
+
+        let s = s.trim();
+        let (s, forced_unit) = match s.strip_suffix(']').and_then(|s| s.rsplit_once('[')) {
+            Some((rest, unit)) => match unit.parse::<UnitSystem>() {
+                Ok(unit) => (rest.trim_end(), Some(unit)),
+                Err(_) => (s, None),
+            },
+            None => (s, None),
+        };
+        let marker = s.len().saturating_sub(Self::OPTIONAL_MARKER.len());
+        let (s, optional) = match s.get(marker..) {
+            Some(m) if m.eq_ignore_ascii_case(Self::OPTIONAL_MARKER) => {
+                (s[..marker].trim_end(), true)
+            }
+            _ => (s, false),
+        };
+
+        if s.is_empty() {
+            return Err(ExpectedIngredient);
         }
-        write!(f, "{}", self.ingredient)
+
+        let (quantity, rest) = IngredientQuantity::parse_leading(s);
+        let original = quantity
+            .as_ref()
+            .map(|_| s[..s.len() - rest.len()].trim_end());
+        let (ingredient, prep) = match rest.split_once(',') {
+            Some((ingredient, prep)) => (ingredient.trim_end(), Some(prep.trim())),
+            None => (rest, None),
+        };
+        let (ingredient, prep, qualifier) = match prep.map(split_qualifier) {
+            Some((prep, Some(qualifier))) => (ingredient, Some(prep), Some(qualifier)),
+            _ => {
+                let (ingredient, qualifier) = split_qualifier(ingredient);
+                (ingredient, prep, qualifier)
+            }
+        };
+
+        let mut alternatives = ingredient.split(" or ");
+        let ingredient = alternatives.next().unwrap_or_default();
+        let alternatives = alternatives.filter_map(|a| a.parse().ok()).collect();
+
+        let (ingredient, sub_recipes) = SubRecipeRef::replace_all(ingredient);
+
+        Ok(Self {
+            ingredient,
+            prep: prep.filter(|p| !p.is_empty()).map(Into::into),
+            quantity,
+            original: original.map(Into::into),
+            qualifier: qualifier.map(Into::into),
+            alternatives,
+            optional,
+            sub_recipe: sub_recipes.into_iter().next(),
+            forced_unit,
+        })
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Ingredients<T = Metric> {
-    pub sections: HashMap<String, Vec<Ingredient<T>>>,
-}
+/// Builds ingredients in code, like `Ingredient::new("flour").weight_g(200.0)`.
+/// Quantities are kept as if written in the recipe file's own format, so a
+/// built ingredient equals the same line parsed.
+impl Ingredient {
+    /// An ingredient without a quantity
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            ingredient: name.into(),
+            prep: None,
+            quantity: None,
+            original: None,
+            qualifier: None,
+            alternatives: Vec::new(),
+            optional: false,
+            sub_recipe: None,
+            forced_unit: None,
+        }
+    }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Step {
-    pub body: String,
-}
+    pub fn weight_g(self, grams: f64) -> Self {
+        let weight = Weight::new_metric((grams * 1000.0).round() as u64);
+        self.with_quantity(IngredientQuantity::Weight(weight))
+    }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Image {
-    pub href: String,
-}
+    pub fn volume_ml(self, ml: f64) -> Self {
+        let volume = Volume::new_metric((ml * 1000.0).round() as u64);
+        self.with_quantity(IngredientQuantity::Volume(volume))
+    }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Recipe<T = Metric> {
-    pub title: String,
-    pub image: Option<Image>,
-    pub introduction: Option<String>,
-    pub ingredients: Vec<Ingredient<T>>,
-    pub steps: Vec<Step>,
-}
+    pub fn count(self, count: f64) -> Self {
+        self.with_quantity(IngredientQuantity::Count(count))
+    }
 
-impl Recipe<Metric> {
-    pub fn as_imperial(self) -> Recipe<Imperial> {
-        let Recipe { title, image, introduction, ingredients, steps } = self;
-        let ingredients = ingredients
-            .into_iter()
-            .map(|i| i.as_imperial())
-            .collect::<Vec<_>>();
+    /// Always shows it in `unit`, like a `[metric]` annotation
+    pub fn forced_unit(self, unit: UnitSystem) -> Self {
+        Self {
+            forced_unit: Some(unit),
+            ..self
+        }
+    }
 
-        Recipe {
-            title,
-            image,
-            introduction,
-            ingredients,
-            steps
+    /// Sets how to prepare it, like `minced`
+    pub fn prep(self, prep: impl Into<String>) -> Self {
+        Self {
+            prep: Some(prep.into()),
+            ..self
+        }
+    }
+
+    fn with_quantity(self, quantity: IngredientQuantity) -> Self {
+        Self {
+            original: Some(quantity.to_recipe_txt()),
+            quantity: Some(quantity),
+            ..self
         }
     }
 }
 
-impl Recipe<Imperial> {
-    pub fn as_metric(self) -> Recipe<Metric> {
-        let Recipe { title, image, introduction, ingredients, steps } = self;
-        let ingredients = ingredients
-            .into_iter()
-            .map(|i| i.as_metric())
-            .collect::<Vec<_>>();
+/// The alternate form, `{:#}`, writes metric weights with the precise display.
+impl<T> std::fmt::Display for Ingredient<T>
+where
+    IngredientQuantity<T>: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.quantity {
+            Some(q) if f.alternate() => write!(f, "{q:#} ")?,
+            Some(q) => write!(f, "{q} ")?,
+            None => {}
+        }
 
-        Recipe {
-            title,
-            image,
-            introduction,
-            ingredients,
-            steps
+        match &self.quantity {
+            Some(IngredientQuantity::Count(c)) => {
+                write!(f, "{}", inflect_name(&self.ingredient, *c != 1.0))?
+            }
+            Some(IngredientQuantity::Range(low, _))
+                if matches!(**low, IngredientQuantity::Count(_)) =>
+            {
+                write!(f, "{}", inflect_name(&self.ingredient, true))?
+            }
+            _ => write!(f, "{}", self.ingredient)?,
+        }
+
+        if !self.alternatives.is_empty() {
+            let alternatives: Vec<_> = self.alternatives.iter().map(|a| a.to_string()).collect();
+            write!(f, " (or {})", alternatives.join(" or "))?;
+        }
+
+        if let Some(prep) = &self.prep {
+            write!(f, ", {prep}")?;
+        }
+
+        if let Some(qualifier) = &self.qualifier {
+            write!(f, ", {qualifier}")?;
+        }
+
+        if self.optional {
+            write!(f, " {}", Self::OPTIONAL_MARKER)?;
         }
+        Ok(())
     }
 }
 
-impl FromStr for Recipe {
-    type Err = RecipeError;
+/// Phrases that say how much of an ingredient to use instead of a quantity
+const QUALIFIERS: &[&str] = &[
+    "to taste",
+    "as needed",
+    "as required",
+    "for garnish",
+    "to garnish",
+    "for serving",
+    "to serve",
+];
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use RecipeError::*;
+/// Splits a trailing qualifier like `to taste` off `s`, along with the comma
+/// or space before it
+fn split_qualifier(s: &str) -> (&str, Option<&str>) {
+    for qualifier in QUALIFIERS {
+        let Some(start) = s.len().checked_sub(qualifier.len()) else {
+            continue;
+        };
+        let Some((rest, found)) = s.split_at_checked(start) else {
+            continue;
+        };
 
-        let title_end = s.find("\n\n").ok_or(ExpectedTitle)?;
-        let title = s[..title_end].to_string();
+        if found.eq_ignore_ascii_case(qualifier) && (rest.is_empty() || rest.ends_with([' ', ',']))
+        {
+            let rest = rest.trim_end().trim_end_matches(',').trim_end();
+            return (rest, Some(found));
+        }
+    }
 
-        println!("{title}");
+    (s, None)
+}
 
-        let s = s[title_end..].trim_start();
+/// Plurals that don't just add an `s`, as `(singular, plural)`
+const IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("leaf", "leaves"),
+    ("loaf", "loaves"),
+    ("half", "halves"),
+    ("knife", "knives"),
+    ("potato", "potatoes"),
+    ("tomato", "tomatoes"),
+    ("mango", "mangoes"),
+    ("mouse", "mice"),
+];
 
-        let (image, s) = if s.starts_with("image:") {
-            let image_href_end = s.find("\n\n").ok_or(ExpectedImageHref)?;
-            (
-                Some(Image {
-                    href: s[6..image_href_end].trim_start().to_string(),
-                }),
-                s[image_href_end..].trim_start(),
-            )
-        } else {
-            (None, s)
-        };
+/// Words that count the ingredient after them, like `cloves` in `3 cloves
+/// garlic`. These are inflected instead of the ingredient itself.
+const COUNTER_WORDS: &[&str] = &[
+    "clove", "slice", "sprig", "stalk", "head", "bunch", "can", "piece", "leaf", "stick", "sheet",
+    "handful", "fillet",
+];
 
-        println!("{image:?}");
+/// Nouns that read the same however many there are, like `2 salmon`
+const UNCOUNTABLE_NOUNS: &[&str] = &[
+    "rice",
+    "garlic",
+    "salmon",
+    "tuna",
+    "cod",
+    "trout",
+    "fish",
+    "shrimp",
+    "milk",
+    "water",
+    "flour",
+    "sugar",
+    "salt",
+    "butter",
+    "bread",
+    "pasta",
+    "spaghetti",
+    "broccoli",
+    "spinach",
+    "kale",
+    "yeast",
+    "honey",
+    "oil",
+];
 
-        let (introduction, s) = if !s.starts_with("---ingredients") {
-            let introduction_end = s.find("\n\n").ok_or(ExpectedImageHref)?;
-            (
-                Some(s[..introduction_end].trim_start().to_string()),
-                s[introduction_end..].trim_start(),
-            )
-        } else {
-            (None, s)
-        };
+fn singular(word: &str) -> String {
+    if let Some((singular, _)) = IRREGULAR_PLURALS.iter().find(|(_, p)| *p == word) {
+        return singular.to_string();
+    }
 
-        println!("{introduction:?}");
+    if let Some(stem) = word.strip_suffix("ies").filter(|s| s.len() > 1) {
+        return format!("{stem}y");
+    }
 
-        if !s.starts_with("---ingredients") {
-            return Err(ExpectedIngredientsStart);
+    for ending in ["ches", "shes", "sses", "xes", "zes"] {
+        if word.ends_with(ending) {
+            return word[..word.len() - 2].to_string();
         }
+    }
 
-        let mut s = s[14..].trim_start();
-        let mut ingredients = Vec::new();
-        while !s.starts_with('\n') {
-            let ingredient_end = s.find('\n').ok_or(UnexpectedEOF("Ingredient".into()))?;
-            let ingredient = s[..ingredient_end].parse::<Ingredient>()?;
-            ingredients.push(ingredient);
-            s = &s[(ingredient_end + 1)..];
+    match word.strip_suffix('s') {
+        Some(stem) if !stem.ends_with('s') && !stem.ends_with('u') && stem.len() > 1 => {
+            stem.to_string()
         }
-        println!("{ingredients:?}");
+        _ => word.to_string(),
+    }
+}
 
-        let s = s.trim_start();
+fn plural(word: &str) -> String {
+    let word = singular(word);
 
-        if !s.starts_with("---steps") {
-            return Err(ExpectedStepsStart);
+    if let Some((_, plural)) = IRREGULAR_PLURALS.iter().find(|(s, _)| *s == word) {
+        return plural.to_string();
+    }
+
+    let consonant_y = word
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']));
+
+    match consonant_y {
+        Some(stem) => format!("{stem}ies"),
+        None if word.ends_with(['s', 'x', 'z']) || word.ends_with("ch") || word.ends_with("sh") => {
+            format!("{word}es")
         }
-        let s = s[8..].trim();
+        None => format!("{word}s"),
+    }
+}
 
-        let steps = s
-            .split("\n\n")
-            .map(|s| Step {
-                body: s.to_string(),
-            })
-            .collect::<Vec<_>>();
+/// Names that mean the same thing at the shop, as `(name, canonical name)`.
+/// Names are matched after being lowercased and singularized.
+const INGREDIENT_SYNONYMS: &[(&str, &str)] = &[
+    ("roma tomato", "tomato"),
+    ("plum tomato", "tomato"),
+    ("scallion", "spring onion"),
+    ("green onion", "spring onion"),
+    ("cilantro", "coriander"),
+    ("eggplant", "aubergine"),
+    ("zucchini", "courgette"),
+    ("capsicum", "bell pepper"),
+    ("garbanzo bean", "chickpea"),
+    ("all-purpose flour", "plain flour"),
+    ("powdered sugar", "icing sugar"),
+    ("confectioners sugar", "icing sugar"),
+];
 
-        println!("{steps:?}");
+/// The name an ingredient is grouped under when searching or adding up a
+/// shopping list, so `Tomatoes`, `tomato` and `roma tomato` are the same thing.
+/// Only meant as a key, ingredients keep their own name for display.
+pub fn canonical_ingredient(name: &str) -> String {
+    let name = name
+        .split_whitespace()
+        .map(|w| singular(&w.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" ");
 
-        Ok(Self {
-            title,
-            image,
-            introduction,
-            ingredients,
-            steps,
-        })
+    match INGREDIENT_SYNONYMS
+        .iter()
+        .find(|(synonym, _)| *synonym == name)
+    {
+        Some((_, canonical)) => canonical.to_string(),
+        None => name,
     }
 }
 
+/// Where `text` mentions one of `ingredients` by name, as byte ranges paired
+/// with the index of the ingredient mentioned. Names match ignoring case and
+/// plurals, and when matches overlap the longest wins, so `soy sauce` is found
+/// rather than `sauce`.
+pub fn ingredient_mentions(
+    text: &str,
+    ingredients: &[&str],
+) -> Vec<(std::ops::Range<usize>, usize)> {
+    let names = ingredients
+        .iter()
+        .map(|i| canonical_ingredient(i))
+        .collect::<Vec<_>>();
+    let longest = names
+        .iter()
+        .map(|n| n.split_whitespace().count())
+        .max()
+        .unwrap_or_default();
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '-';
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, is_word(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                words.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let mut mentions = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let found = (1..=longest.min(words.len() - i)).rev().find_map(|n| {
+            let range = words[i].start..words[i + n - 1].end;
+            let spaced = words[i..i + n]
+                .windows(2)
+                .all(|w| text[w[0].end..w[1].start].trim().is_empty());
+            let name = canonical_ingredient(&text[range.clone()]);
+
+            names
+                .iter()
+                .position(|n| !n.is_empty() && *n == name)
+                .filter(|_| spaced)
+                .map(|index| (range, index, n))
+        });
+
+        match found {
+            Some((range, index, n)) => {
+                mentions.push((range, index));
+                i += n;
+            }
+            None => i += 1,
+        }
+    }
+
+    mentions
+}
+
+/// Makes a counted ingredient name agree with its count, so `1 egg` but
+/// `2 eggs`, and `1 clove garlic` but `3 cloves garlic`. Only the last word is
+/// inflected, unless the name starts with a counter word like `clove` or is
+/// what's counted in an `X of Y` phrase, like `boxes` in `2 boxes of pasta`.
+/// [Uncountable nouns](UNCOUNTABLE_NOUNS) are left as they are.
+fn inflect_name(name: &str, is_plural: bool) -> String {
+    let inflect = |word: &str| match (
+        is_plural,
+        UNCOUNTABLE_NOUNS.contains(&word.to_lowercase().as_str()),
+    ) {
+        (_, true) => word.to_string(),
+        (true, false) => plural(word),
+        (false, false) => singular(word),
+    };
+
+    let (first, rest) = name.split_once(' ').unwrap_or((name, ""));
+
+    if !rest.is_empty() && COUNTER_WORDS.contains(&singular(&first.to_lowercase()).as_str()) {
+        return format!("{} {rest}", inflect(first));
+    }
+
+    if let Some((counted, of)) = name.split_once(" of ") {
+        return format!("{} of {of}", inflect_name(counted, is_plural));
+    }
+
+    match name.rsplit_once(' ') {
+        Some((start, last)) => format!("{start} {}", inflect(last)),
+        None => inflect(name),
+    }
+}
+
+/// A named group of ingredients, like `# Sauce`. Ingredients listed before
+/// any heading go into a section with an empty name.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IngredientSection<T = Metric> {
+    pub name: String,
+    pub ingredients: Vec<Ingredient<T>>,
+    /// Marked with a trailing `(no-scale)` on the heading, like `# Garnish
+    /// (no-scale)`, for amounts that stay the same however much is made
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub no_scale: bool,
+}
+
+impl<T> IngredientSection<T> {
+    pub const NO_SCALE_MARKER: &'static str = "(no-scale)";
+
+    pub fn as_imperial(self) -> IngredientSection<Imperial> {
+        IngredientSection {
+            name: self.name,
+            ingredients: self
+                .ingredients
+                .into_iter()
+                .map(|i| i.as_imperial())
+                .collect(),
+            no_scale: self.no_scale,
+        }
+    }
+
+    pub fn as_metric(self) -> IngredientSection<Metric> {
+        IngredientSection {
+            name: self.name,
+            ingredients: self
+                .ingredients
+                .into_iter()
+                .map(|i| i.as_metric())
+                .collect(),
+            no_scale: self.no_scale,
+        }
+    }
+
+    pub fn as_imperial_uk(self) -> IngredientSection<ImperialUk> {
+        IngredientSection {
+            name: self.name,
+            ingredients: self
+                .ingredients
+                .into_iter()
+                .map(|i| i.as_imperial_uk())
+                .collect(),
+            no_scale: self.no_scale,
+        }
+    }
+}
+
+impl<T: Copy> IngredientSection<T> {
+    /// Scales every ingredient, unless the section is [`no_scale`](Self::no_scale)
+    pub fn scale(&self, factor: f64) -> Self {
+        if self.no_scale {
+            return self.clone();
+        }
+
+        IngredientSection {
+            name: self.name.clone(),
+            ingredients: self.ingredients.iter().map(|i| i.scale(factor)).collect(),
+            no_scale: false,
+        }
+    }
+}
+
+/// Sections are kept in the order they appear in the recipe file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ingredients<T = Metric> {
+    pub sections: Vec<IngredientSection<T>>,
+}
+
+impl<T> Ingredients<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &Ingredient<T>> {
+        self.sections.iter().flat_map(|s| s.ingredients.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.sections.iter().map(|s| s.ingredients.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_imperial(self) -> Ingredients<Imperial> {
+        Ingredients {
+            sections: self.sections.into_iter().map(|s| s.as_imperial()).collect(),
+        }
+    }
+
+    pub fn as_metric(self) -> Ingredients<Metric> {
+        Ingredients {
+            sections: self.sections.into_iter().map(|s| s.as_metric()).collect(),
+        }
+    }
+
+    pub fn as_imperial_uk(self) -> Ingredients<ImperialUk> {
+        Ingredients {
+            sections: self
+                .sections
+                .into_iter()
+                .map(|s| s.as_imperial_uk())
+                .collect(),
+        }
+    }
+}
+
+impl<T: Copy> Ingredients<T> {
+    pub fn scale(&self, factor: f64) -> Self {
+        Ingredients {
+            sections: self.sections.iter().map(|s| s.scale(factor)).collect(),
+        }
+    }
+
+    /// Every ingredient on one line in the given unit system, separated by
+    /// commas, with named sections prefixed and separated by semicolons
+    pub fn summary(&self, unit: UnitSystem) -> String {
+        self.sections
+            .iter()
+            .filter(|s| !s.ingredients.is_empty())
+            .map(|s| {
+                let list = s
+                    .ingredients
+                    .iter()
+                    .map(|i| i.to_string_in(unit))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                match s.name.is_empty() {
+                    true => list,
+                    false => format!("{}: {list}", s.name),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Step {
+    pub body: String,
+    /// Indented or `- ` bulleted lines of the step, shown as a nested list
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub substeps: Vec<String>,
+}
+
+/// Parses one step of the `---steps` section. Its first line always belongs to
+/// the body, later lines are substeps when they're indented or start with `- `.
+impl From<&str> for Step {
+    fn from(s: &str) -> Self {
+        let mut lines = s.lines();
+        let mut body = lines.next().unwrap_or_default().trim().to_string();
+        let mut substeps = Vec::new();
+
+        for line in lines {
+            let trimmed = line.trim();
+
+            match trimmed.strip_prefix("- ") {
+                Some(substep) => substeps.push(substep.trim_start().to_string()),
+                None if line.starts_with([' ', '\t']) => substeps.push(trimmed.to_string()),
+                None => {
+                    body.push('\n');
+                    body.push_str(trimmed);
+                }
+            }
+        }
+
+        Step { body, substeps }
+    }
+}
+
+impl Step {
+    /// The body followed by the substeps, one per line
+    pub fn text(&self) -> String {
+        std::iter::once(self.body.as_str())
+            .chain(self.substeps.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Writes the step like in the `---steps` section, with `- ` bullets for
+    /// the substeps
+    pub fn to_recipe_txt(&self) -> String {
+        let substeps = self.substeps.iter().map(|s| format!("\n- {s}"));
+        std::iter::once(self.body.clone()).chain(substeps).collect()
+    }
+
+    /// Finds durations mentioned in the step, like `simmer for 20 minutes` or
+    /// `rest 1 hour 30 minutes`, in the order they appear. Amounts directly
+    /// followed by another amount are combined into one duration.
+    pub fn durations(&self) -> Vec<Duration> {
+        let text = self.text();
+        let words = text
+            .split_whitespace()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '.'))
+            .map(|w| w.trim_end_matches('.'))
+            .collect::<Vec<_>>();
+
+        let mut durations = Vec::new();
+        let mut i = 0;
+
+        while i < words.len() {
+            let mut total = None::<Duration>;
+
+            while let Some((duration, len)) = leading_duration(&words[i..]) {
+                total = Some(total.map_or(duration, |t| t + duration));
+                i += len;
+            }
+
+            match total {
+                Some(total) => durations.push(total),
+                None => i += 1,
+            }
+        }
+
+        durations
+    }
+
+    /// Other recipes the step refers to, like `[[béchamel]]`
+    pub fn sub_recipes(&self) -> Vec<SubRecipeRef> {
+        SubRecipeRef::find_all(&self.text())
+    }
+}
+
+/// Temperatures written without a degree sign below this many degrees are left
+/// alone, since they're more likely cups, like `2 C flour`
+const MIN_BARE_TEMPERATURE: f64 = 30.0;
+
+/// Rewrites temperatures in `text`, like `preheat to 200C`, in Fahrenheit for
+/// US imperial and Celsius otherwise. A temperature is a number or range like
+/// `180-200` directly followed by `C` or `F`, with or without a space or a
+/// degree sign between, and not followed by more letters. Ones already in the
+/// right scale are left as written, and so are pairs already written in both,
+/// like `200C/400F`.
+pub fn convert_temperatures(text: &str, unit: UnitSystem) -> String {
+    let mut converted = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (i, c) in text.char_indices() {
+        let at_word_start = !text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|p| p.is_alphanumeric() || p == '.' || p == ',');
+
+        if i < last || !c.is_ascii_digit() || !at_word_start {
+            continue;
+        }
+
+        if let Some(len) = temperature_pair(&text[i..]) {
+            converted.push_str(&text[last..i + len]);
+            last = i + len;
+        } else if let Some((len, temperature)) = leading_temperature(&text[i..], unit) {
+            converted.push_str(&text[last..i]);
+            converted.push_str(&temperature);
+            last = i + len;
+        }
+    }
+
+    converted.push_str(&text[last..]);
+    converted
+}
+
+/// How many bytes the temperatures at the start of `s` take up when they're the
+/// same temperature in both scales, like `200C/400F`
+fn temperature_pair(s: &str) -> Option<usize> {
+    let (len, first) = written_temperature(s)?;
+    let (second_len, second) = written_temperature(s[len..].strip_prefix('/')?)?;
+
+    (first.celsius != second.celsius).then_some(len + 1 + second_len)
+}
+
+/// A temperature as it's written in a step, before it's converted
+struct WrittenTemperature {
+    low: f64,
+    /// The high end of a range, and the dash it was written with
+    high: Option<(f64, char)>,
+    scale: char,
+    celsius: bool,
+}
+
+/// The temperature at the start of `s` as it's written, along with how many
+/// bytes it took up. See [`convert_temperatures`].
+fn written_temperature(s: &str) -> Option<(usize, WrittenTemperature)> {
+    let number = |s: &str| {
+        let len = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let len = s[..len].trim_end_matches('.').len();
+        Some((s[..len].parse::<f64>().ok()?, len))
+    };
+
+    let (low, mut len) = number(s)?;
+    let mut high = None;
+    if let Some(dash) = s[len..].chars().next().filter(|&c| c == '-' || c == '–') {
+        if let Some((amount, high_len)) = number(&s[len + dash.len_utf8()..]) {
+            high = Some((amount, dash));
+            len += dash.len_utf8() + high_len;
+        }
+    }
+
+    let rest = &s[len..];
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+    let (degrees, rest) = match rest.strip_prefix('°') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    let scale = rest.chars().next()?;
+    let celsius = match scale {
+        'C' => true,
+        'F' => false,
+        'c' if degrees => true,
+        'f' if degrees => false,
+        _ => return None,
+    };
+    if rest[1..].starts_with(char::is_alphanumeric) || (!degrees && low < MIN_BARE_TEMPERATURE) {
+        return None;
+    }
+
+    let written = WrittenTemperature {
+        low,
+        high,
+        scale,
+        celsius,
+    };
+    Some((s.len() - rest.len() + 1, written))
+}
+
+/// The temperature at the start of `s` written for `unit`, along with how many
+/// bytes it took up. See [`convert_temperatures`].
+fn leading_temperature(s: &str, unit: UnitSystem) -> Option<(usize, String)> {
+    let (
+        len,
+        WrittenTemperature {
+            low,
+            high,
+            scale,
+            celsius,
+        },
+    ) = written_temperature(s)?;
+    if celsius == (unit != UnitSystem::Imperial) {
+        return None;
+    }
+
+    let parse = |amount: f64| format!("{amount}{scale}").parse::<Temperature>().ok();
+    let low = parse(low)?;
+
+    let temperature = match (high, celsius) {
+        (None, true) => low.as_imperial().to_string(),
+        (None, false) => low.to_string(),
+        (Some((high, dash)), true) => format!(
+            "{:.0}{dash}{}",
+            low.fahrenheit(),
+            parse(high)?.as_imperial()
+        ),
+        (Some((high, dash)), false) => {
+            format!("{:.0}{dash}{}", low.celsius(), parse(high)?)
+        }
+    };
+
+    Some((len, temperature))
+}
+
+/// What a shared recipe link asks for, from a query string like
+/// `unit=imperial&scale=2`. Anything missing or invalid is left at its
+/// default: the reader's own unit system and the recipe as written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShareOptions {
+    pub unit: Option<UnitSystem>,
+    pub scale: f64,
+}
+
+impl Default for ShareOptions {
+    fn default() -> Self {
+        Self {
+            unit: None,
+            scale: 1.0,
+        }
+    }
+}
+
+impl ShareOptions {
+    /// Reads the options from a query string, with or without its leading `?`
+    pub fn from_query(query: &str) -> Self {
+        let mut options = Self::default();
+        let pairs = query
+            .trim_start_matches('?')
+            .split('&')
+            .filter_map(|pair| pair.split_once('='));
+
+        for (key, value) in pairs {
+            match key {
+                "unit" => options.unit = value.parse().ok(),
+                "scale" => {
+                    options.scale = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|s| s.is_finite() && *s > 0.0)
+                        .unwrap_or(1.0)
+                }
+                _ => {}
+            }
+        }
+
+        options
+    }
+
+    /// The query string for the options, without a leading `?`. Defaults are
+    /// left out, so it's empty when there's nothing to share.
+    pub fn to_query(&self) -> String {
+        let unit = self.unit.map(|u| format!("unit={u}"));
+        let scale = (self.scale != 1.0).then(|| format!("scale={}", self.scale));
+
+        [unit, scale]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// Where the site is served from, like `/recipe-book` when it isn't at the root
+/// of its domain. It's read from `RECIPE_BOOK_BASE_PATH` at build time, which
+/// should match the `--public-url` given to trunk.
+pub const BASE_PATH: &str = match option_env!("RECIPE_BOOK_BASE_PATH") {
+    Some(base) => base,
+    None => "",
+};
+
+/// Joins `path` onto `base` with a single `/` between them, giving an absolute
+/// path like `/recipe-book/recipes/egg_fried_rice.txt`
+pub fn join_path(base: &str, path: &str) -> String {
+    let base = base.trim_matches('/');
+    let path = path.trim_start_matches('/');
+
+    match base.is_empty() {
+        true => format!("/{path}"),
+        false => format!("/{base}/{path}"),
+    }
+}
+
+/// The route of the recipe `name`, without the [`BASE_PATH`]. Router links
+/// like `<A>` and `<Redirect>` resolve it against the `<Router base>`, anything
+/// else has to [join](join_path) it onto the base itself.
+pub fn recipe_route(name: &str) -> String {
+    format!("/recipe/{name}")
+}
+
+/// Where the `.txt` file for the recipe `name` is served from under `base`. The
+/// app caches loaded recipes by this URL, so everything loading or prefetching
+/// a recipe has to go through here to share the cached copy.
+pub fn recipe_file_url(base: &str, name: &str) -> String {
+    join_path(base, &format!("recipes/{name}.txt"))
+}
+
+/// `path` relative to the site, so `/recipe-book/list` is `/list` under a base
+/// of `/recipe-book`. Paths outside of `base` are returned as they are.
+pub fn strip_base<'a>(base: &str, path: &'a str) -> &'a str {
+    let base = base.trim_matches('/');
+
+    match path.trim_start_matches('/').strip_prefix(base) {
+        Some(rest) if !base.is_empty() && (rest.is_empty() || rest.starts_with('/')) => {
+            &path[path.len() - rest.len()..]
+        }
+        _ => path,
+    }
+}
+
+/// A reference to another recipe, written `[[name]]` in an ingredient or step
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubRecipeRef {
+    pub name: String,
+}
+
+impl SubRecipeRef {
+    /// Parses a `[[name]]` reference from the start of `s`, returning it along
+    /// with the rest of `s`
+    pub fn parse_leading(s: &str) -> Option<(Self, &str)> {
+        let (name, rest) = s.strip_prefix("[[")?.split_once("]]")?;
+        let name = name.trim();
+
+        (!name.is_empty()).then(|| (Self { name: name.into() }, rest))
+    }
+
+    /// Every reference in `text`, in the order they appear
+    pub fn find_all(text: &str) -> Vec<Self> {
+        Self::replace_all(text).1
+    }
+
+    /// Replaces every reference in `text` with the plain name it refers to,
+    /// returning the text along with the references
+    pub fn replace_all(text: &str) -> (String, Vec<Self>) {
+        let mut replaced = String::with_capacity(text.len());
+        let mut refs = Vec::new();
+        let mut rest = text;
+
+        while let Some(i) = rest.find('[') {
+            replaced.push_str(&rest[..i]);
+            let tail = &rest[i..];
+
+            match Self::parse_leading(tail) {
+                Some((r, after)) => {
+                    replaced.push_str(&r.name);
+                    refs.push(r);
+                    rest = after;
+                }
+                None => {
+                    replaced.push_str(&tail[..1]);
+                    rest = &tail[1..];
+                }
+            }
+        }
+
+        replaced.push_str(rest);
+        (replaced, refs)
+    }
+
+    /// The file name of the recipe it points to, like `egg_fried_rice` for
+    /// `[[Egg fried rice]]`
+    pub fn slug(&self) -> String {
+        self.name.to_lowercase().replace(char::is_whitespace, "_")
+    }
+
+    /// The route of the recipe it points to, without the [`BASE_PATH`]
+    pub fn href(&self) -> String {
+        recipe_route(&self.slug())
+    }
+}
+
+/// Parses a duration from the start of `words`, either a single word like
+/// `20m` or an amount followed by a unit. Returns the number of words used.
+fn leading_duration(words: &[&str]) -> Option<(Duration, usize)> {
+    let first = words.first()?;
+
+    if !first.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if let Some(next) = words.get(1) {
+        if let Ok(duration) = format!("{first} {next}").parse::<Duration>() {
+            return Some((duration, 2));
+        }
+    }
+
+    first.parse::<Duration>().ok().map(|d| (d, 1))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = RecipeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(RecipeError::InvalidDifficulty(s.trim().to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Easy => write!(f, "Easy"),
+            Self::Medium => write!(f, "Medium"),
+            Self::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+/// Nutritional totals for the whole recipe. Energy is in kcal, everything else
+/// in grams.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nutrition {
+    pub calories: Option<f64>,
+    pub protein: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbohydrates: Option<f64>,
+    pub fiber: Option<f64>,
+    pub sugar: Option<f64>,
+    pub sodium: Option<f64>,
+}
+
+impl Nutrition {
+    fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Nutrition {
+            calories: self.calories.map(&f),
+            protein: self.protein.map(&f),
+            fat: self.fat.map(&f),
+            carbohydrates: self.carbohydrates.map(&f),
+            fiber: self.fiber.map(&f),
+            sugar: self.sugar.map(&f),
+            sodium: self.sodium.map(&f),
+        }
+    }
+
+    /// Every known value with its label and unit, in the order of a nutrition
+    /// facts panel
+    pub fn facts(&self) -> Vec<(&'static str, f64, &'static str)> {
+        [
+            ("Calories", self.calories, "kcal"),
+            ("Protein", self.protein, "g"),
+            ("Fat", self.fat, "g"),
+            ("Carbohydrates", self.carbohydrates, "g"),
+            ("Fiber", self.fiber, "g"),
+            ("Sugar", self.sugar, "g"),
+            ("Sodium", self.sodium.map(|g| g * 1000.0), "mg"),
+        ]
+        .into_iter()
+        .filter_map(|(label, value, unit)| Some((label, value?, unit)))
+        .collect()
+    }
+
+    /// Parses a `key: value` line from the `---nutrition` section. Values may
+    /// have a `g`, `mg` or `kcal` unit, and default to grams and kcal. `salt`
+    /// is converted to the sodium it contains.
+    fn parse_line(&mut self, line: &str) -> Result<(), RecipeError> {
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| RecipeError::UnknownNutrient(line.trim().to_string()))?;
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+
+        let invalid = || RecipeError::InvalidNutrition {
+            key: key.clone(),
+            value: value.to_string(),
+        };
+
+        let number_end = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let amount = value[..number_end].parse::<f64>().map_err(|_| invalid())?;
+        let amount = match value[number_end..].trim().to_lowercase().as_str() {
+            "" | "g" | "kcal" | "cal" | "calories" => amount,
+            "mg" => amount / 1000.0,
+            _ => return Err(invalid()),
+        };
+        // Salt is stored as the sodium in it, which is about 40% of its weight
+        let amount = match key.as_str() {
+            "salt" => amount / 2.5,
+            _ => amount,
+        };
+
+        let field = match key.as_str() {
+            "calories" | "energy" | "kcal" => &mut self.calories,
+            "protein" => &mut self.protein,
+            "fat" => &mut self.fat,
+            "carbohydrates" | "carbs" => &mut self.carbohydrates,
+            "fiber" | "fibre" => &mut self.fiber,
+            "sugar" | "sugars" => &mut self.sugar,
+            "sodium" | "salt" => &mut self.sodium,
+            _ => return Err(RecipeError::UnknownNutrient(key)),
+        };
+        *field = Some(amount);
+
+        Ok(())
+    }
+}
+
+impl std::ops::Mul<f64> for Nutrition {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        self.map(|v| v * rhs)
+    }
+}
+
+impl std::ops::Div<f64> for Nutrition {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        self.map(|v| v / rhs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Image {
+    pub href: String,
+    /// Written after a `|` in the `image:` header
+    pub alt: Option<String>,
+}
+
+impl FromStr for Image {
+    type Err = RecipeError;
+
+    /// Parses `href | alt text`, where the alt text is optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (href, alt) = match s.split_once('|') {
+            Some((href, alt)) => (href.trim(), Some(alt.trim().to_string())),
+            None => (s.trim(), None),
+        };
+
+        if href.is_empty() {
+            return Err(RecipeError::ExpectedImageHref);
+        }
+
+        Ok(Image {
+            href: href.to_string(),
+            alt: alt.filter(|a| !a.is_empty()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Recipe<T = Metric> {
+    pub title: String,
+    pub image: Option<Image>,
+    pub introduction: Option<String>,
+    pub servings: Option<u32>,
+    /// Lowercase and deduplicated, in the order they were written
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub difficulty: Option<Difficulty>,
+    /// From 0 to 5
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rating: Option<f32>,
+    pub prep_time: Option<Duration>,
+    pub cook_time: Option<Duration>,
+    pub ingredients: Ingredients<T>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nutrition: Option<Nutrition>,
+    pub steps: Vec<Step>,
+}
+
+impl<T: Copy> Recipe<T> {
+    /// Every ingredient once, with repeats across sections summed where their
+    /// quantities can be, like `1 tsp salt` and `1/2 tsp salt` becoming
+    /// `1 1/2 tsp salt`. Sections are dropped, in the order of first mention.
+    pub fn consolidated_ingredients(&self) -> Vec<Ingredient<T>> {
+        merge_ingredients(self.ingredients.iter())
+    }
+
+    /// The ingredients on a single line, for pasting into a message. See
+    /// [`Ingredients::summary`].
+    pub fn ingredients_summary(&self, unit: UnitSystem) -> String {
+        self.ingredients.summary(unit)
+    }
+
+    /// Scales every ingredient, the nutrition and the servings by `factor`,
+    /// rounding the ingredient quantities as `rounding` says and the servings to
+    /// a whole number, so the nutrition per serving stays about the same.
+    /// Quantities are rounded for the unit system they were written in, or the
+    /// one most of the recipe was written in. An unscaled recipe is left as
+    /// written.
+    pub fn scale(&self, factor: f64, rounding: Rounding) -> Recipe<T> {
+        let mut ingredients = self.ingredients.scale(factor);
+        if factor != 1.0 {
+            let dominant = self.dominant_unit_system();
+            let ingredients = self
+                .ingredients
+                .sections
+                .iter()
+                .zip(&mut ingredients.sections)
+                .filter(|(s, _)| !s.no_scale)
+                .flat_map(|(s, scaled)| s.ingredients.iter().zip(&mut scaled.ingredients));
+
+            for (ingredient, scaled) in ingredients {
+                let unit = ingredient
+                    .written_unit_system()
+                    .or(dominant)
+                    .unwrap_or(UnitSystem::Imperial);
+                let rounding = match rounding {
+                    Rounding::FriendlyFractions if ingredient.only_comes_whole() => {
+                        Rounding::NearestWhole
+                    }
+                    rounding => rounding,
+                };
+
+                scaled.quantity = scaled.quantity.as_ref().map(|q| q.round(rounding, unit));
+            }
+        }
+
+        Recipe {
+            title: self.title.clone(),
+            image: self.image.clone(),
+            introduction: self.introduction.clone(),
+            servings: self.servings.map(|s| match s {
+                0 => 0,
+                s => ((s as f64 * factor).round() as u32).max(1),
+            }),
+            tags: self.tags.clone(),
+            difficulty: self.difficulty,
+            rating: self.rating,
+            prep_time: self.prep_time,
+            cook_time: self.cook_time,
+            ingredients,
+            nutrition: self.nutrition.map(|n| n * factor),
+            steps: self.steps.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Recipe<T> {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Recipe always serializes to JSON")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Recipe<T>
+where
+    Ingredient<T>: std::fmt::Display,
+{
+    /// Describes the recipe as a [schema.org Recipe](https://schema.org/Recipe)
+    /// in JSON-LD, which search engines and other recipe apps understand.
+    /// Fields the recipe doesn't have are left out.
+    pub fn to_schema_org_jsonld(&self) -> String {
+        use serde_json::json;
+
+        let mut ld = json!({
+            "@context": "https://schema.org",
+            "@type": "Recipe",
+            "name": self.title,
+            "recipeIngredient": self.ingredients.iter().map(|i| i.to_string()).collect::<Vec<_>>(),
+            "recipeInstructions": self
+                .steps
+                .iter()
+                .map(|s| json!({ "@type": "HowToStep", "text": s.text() }))
+                .collect::<Vec<_>>(),
+        });
+
+        let optional = [
+            ("image", self.image.as_ref().map(|i| json!(i.href))),
+            ("description", self.introduction.as_ref().map(|i| json!(i))),
+            ("recipeYield", self.servings.map(|s| json!(s.to_string()))),
+            (
+                "keywords",
+                (!self.tags.is_empty()).then(|| json!(self.tags.join(", "))),
+            ),
+            ("prepTime", self.prep_time.map(|d| json!(d.to_iso8601()))),
+            ("cookTime", self.cook_time.map(|d| json!(d.to_iso8601()))),
+            (
+                "totalTime",
+                self.total_time().map(|d| json!(d.to_iso8601())),
+            ),
+            ("nutrition", self.nutrition.map(|n| n.to_schema_org())),
+        ];
+
+        for (key, value) in optional {
+            if let Some(value) = value {
+                ld[key] = value;
+            }
+        }
+
+        ld.to_string()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Nutrition {
+    /// A schema.org `NutritionInformation`, for [`Recipe::to_schema_org_jsonld`]
+    fn to_schema_org(self) -> serde_json::Value {
+        let mut info = serde_json::json!({ "@type": "NutritionInformation" });
+        let facts = [
+            ("calories", self.calories, "kcal"),
+            ("proteinContent", self.protein, "g"),
+            ("fatContent", self.fat, "g"),
+            ("carbohydrateContent", self.carbohydrates, "g"),
+            ("fiberContent", self.fiber, "g"),
+            ("sugarContent", self.sugar, "g"),
+            ("sodiumContent", self.sodium.map(|g| g * 1000.0), "mg"),
+        ];
+
+        for (key, value, unit) in facts {
+            if let Some(value) = value {
+                info[key] = format!("{value} {unit}").into();
+            }
+        }
+
+        info
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Recipe {
+    pub fn from_json(s: &str) -> Result<Recipe, RecipeError> {
+        serde_json::from_str(s).map_err(|e| RecipeError::CustomString(e.to_string()))
+    }
+}
+
+impl Recipe<Metric> {
+    pub fn as_imperial(self) -> Recipe<Imperial> {
+        let Recipe {
+            title,
+            image,
+            introduction,
+            servings,
+            tags,
+            difficulty,
+            rating,
+            prep_time,
+            cook_time,
+            ingredients,
+            nutrition,
+            steps,
+        } = self;
+        let ingredients = ingredients.as_imperial();
+
+        Recipe {
+            title,
+            image,
+            introduction,
+            servings,
+            tags,
+            difficulty,
+            rating,
+            prep_time,
+            cook_time,
+            ingredients,
+            nutrition,
+            steps,
+        }
+    }
+}
+
+impl Recipe<Imperial> {
+    pub fn as_metric(self) -> Recipe<Metric> {
+        let Recipe {
+            title,
+            image,
+            introduction,
+            servings,
+            tags,
+            difficulty,
+            rating,
+            prep_time,
+            cook_time,
+            ingredients,
+            nutrition,
+            steps,
+        } = self;
+        let ingredients = ingredients.as_metric();
+
+        Recipe {
+            title,
+            image,
+            introduction,
+            servings,
+            tags,
+            difficulty,
+            rating,
+            prep_time,
+            cook_time,
+            ingredients,
+            nutrition,
+            steps,
+        }
+    }
+}
+
+impl<T> Recipe<T> {
+    /// The unit system most quantities were written in, going by the
+    /// ingredients' quantities as written. Counts and units every system
+    /// uses, like `tsp`, don't count towards either. `None` when nothing
+    /// counts or it's a tie.
+    pub fn dominant_unit_system(&self) -> Option<UnitSystem> {
+        let (mut metric, mut imperial) = (0, 0);
+
+        for original in self
+            .ingredients
+            .iter()
+            .filter_map(|i| i.original.as_deref())
+        {
+            match (
+                UnitSystem::Metric.uses(original),
+                UnitSystem::Imperial.uses(original),
+            ) {
+                (true, false) => metric += 1,
+                (false, _) => imperial += 1,
+                (true, true) => {}
+            }
+        }
+
+        match metric.cmp(&imperial) {
+            std::cmp::Ordering::Greater => Some(UnitSystem::Metric),
+            std::cmp::Ordering::Less => Some(UnitSystem::Imperial),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Prep and cook time together, if either is known
+    pub fn total_time(&self) -> Option<Duration> {
+        match (self.prep_time, self.cook_time) {
+            (Some(prep), Some(cook)) => Some(prep + cook),
+            (prep, cook) => prep.or(cook),
+        }
+    }
+
+    /// Words in the introduction and steps
+    pub fn word_count(&self) -> usize {
+        let steps = self.steps.iter().map(Step::text);
+
+        self.introduction
+            .clone()
+            .into_iter()
+            .chain(steps)
+            .map(|text| text.split_whitespace().count())
+            .sum()
+    }
+
+    /// Roughly how long reading the introduction and steps takes, in whole
+    /// minutes rounded up. This is how long reading takes, not cooking.
+    pub fn reading_time(&self) -> Duration {
+        const WORDS_PER_MINUTE: usize = 200;
+
+        Duration::from_minutes(self.word_count().div_ceil(WORDS_PER_MINUTE) as u64)
+    }
+
+    /// The total time and servings for the recipe list, like `15m · serves 2`,
+    /// leaving out whichever isn't known
+    pub fn list_summary(&self) -> Option<String> {
+        let parts = [
+            self.total_time().map(|t| t.to_string()),
+            self.servings.map(|s| format!("serves {s}")),
+        ];
+        let parts = parts.into_iter().flatten().collect::<Vec<_>>();
+
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    /// Nutrition for a single serving, when both the totals and the number of
+    /// servings are known
+    pub fn per_serving(&self) -> Option<Nutrition> {
+        let servings = self.servings.filter(|&s| s > 0)?;
+        Some(self.nutrition? / servings as f64)
+    }
+
+    /// The factor to [scale](Self::scale) by to make `target` servings, never
+    /// fewer than one. Recipes without servings count as serving one.
+    pub fn servings_scale(&self, target: u32) -> f64 {
+        let base = self.servings.filter(|&s| s > 0).unwrap_or(1);
+        target.max(1) as f64 / base as f64
+    }
+
+    /// Looks for likely authoring mistakes, like ingredients that are never used
+    /// in the steps. Unlike parse errors, these don't stop the recipe from
+    /// being shown.
+    pub fn validate(&self) -> Vec<RecipeWarning> {
+        use RecipeWarning::*;
+
+        let mut warnings = Vec::new();
+
+        if let Some(image) = &self.image {
+            if !looks_like_href(&image.href) {
+                warnings.push(InvalidImageHref(image.href.clone()));
+            }
+        }
+
+        if self.ingredients.is_empty() {
+            warnings.push(NoIngredients);
+        }
+
+        for ingredient in self.ingredients.iter() {
+            if ingredient.quantity.as_ref().is_some_and(|q| q.is_zero()) {
+                warnings.push(ZeroQuantity(ingredient.ingredient.clone()));
+            }
+        }
+
+        let steps = self
+            .steps
+            .iter()
+            .map(|s| format!(" {} ", words_of(&s.text()).join(" ")))
+            .collect::<Vec<_>>();
+
+        if steps.iter().all(|s| s.trim().is_empty()) {
+            warnings.push(NoSteps);
+            return warnings;
+        }
+
+        for ingredient in self.ingredients.iter() {
+            let used = words_of(&ingredient.ingredient)
+                .iter()
+                .filter(|w| w.len() >= 3)
+                .any(|w| steps.iter().any(|s| s.contains(&format!(" {w} "))));
+
+            if !used {
+                warnings.push(UnusedIngredient(ingredient.ingredient.clone()));
+            }
+        }
+
+        let listed = self
+            .ingredients
+            .iter()
+            .map(|i| format!(" {} ", words_of(&i.ingredient).join(" ")))
+            .collect::<Vec<_>>();
+
+        for (i, step) in steps.iter().enumerate() {
+            for name in KNOWN_INGREDIENTS {
+                let name = format!(" {name} ");
+
+                if step.contains(&name) && !listed.iter().any(|l| l.contains(&name)) {
+                    warnings.push(UnlistedIngredient {
+                        step: i + 1,
+                        ingredient: name.trim().to_string(),
+                    });
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+/// Common ingredients that [`Recipe::validate`] looks for in the steps, written
+/// the way [`words_of`] leaves them
+const KNOWN_INGREDIENTS: &[&str] = &[
+    "water",
+    "milk",
+    "cream",
+    "butter",
+    "oil",
+    "honey",
+    "flour",
+    "sugar",
+    "brown sugar",
+    "powdered sugar",
+    "salt",
+    "pepper",
+    "rice",
+    "oat",
+    "cocoa",
+    "egg",
+    "garlic",
+    "onion",
+    "cheese",
+    "parmesan",
+    "lemon",
+    "lime",
+    "vinegar",
+    "yeast",
+    "baking powder",
+    "baking soda",
+    "vanilla",
+    "cinnamon",
+];
+
+/// Lowercase words with punctuation and plural `s` removed, for loosely
+/// matching ingredient names against step text
+fn words_of(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let w = w.to_lowercase();
+            match w.len() > 3 && w.ends_with('s') && !w.ends_with("ss") {
+                true => w[..w.len() - 1].to_string(),
+                false => w,
+            }
+        })
+        .collect()
+}
+
+fn looks_like_href(href: &str) -> bool {
+    let has_prefix = ["http://", "https://", "/", "./", "../", "data:"]
+        .iter()
+        .any(|p| href.starts_with(p));
+    let is_file = href.rsplit_once('.').is_some_and(|(name, ext)| {
+        !name.is_empty() && !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+
+    !href.contains(char::is_whitespace) && (has_prefix || is_file)
+}
+
+impl FromStr for Recipe {
+    type Err = RecipeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use RecipeError::*;
+
+        // Files saved on Windows parse the same as ones with plain `\n` endings.
+        // Comments are dropped up front, keeping their line numbers so errors
+        // still point at the right line of the file.
+        let mut comments = Vec::new();
+        let input = &s
+            .replace("\r\n", "\n")
+            .split_inclusive('\n')
+            .enumerate()
+            .filter(|&(i, line)| {
+                let comment = is_comment(line);
+                if comment {
+                    comments.push(i + 1);
+                }
+                !comment
+            })
+            .map(|(_, line)| line)
+            .collect::<String>();
+        let s = input.as_str();
+        // 1-based line number of the start of `rest`, which is always a suffix of `input`
+        let at_line = |rest: &str, e: RecipeError| {
+            let mut line = input[..input.len() - rest.len()].matches('\n').count() + 1;
+            for &comment in &comments {
+                if comment <= line {
+                    line += 1;
+                }
+            }
+            AtLine {
+                line,
+                source: Box::new(e),
+            }
+        };
+
+        let title_end = s.find("\n\n").ok_or_else(|| at_line(s, ExpectedTitle))?;
+        let title = s[..title_end].to_string();
+
+        let mut s = s[title_end..].trim_start();
+        let mut image = None;
+        let mut servings = None;
+        let mut tags = Vec::<String>::new();
+        let mut difficulty = None;
+        let mut rating = None;
+        let mut prep_time = None;
+        let mut cook_time = None;
+
+        while let Some((line, rest)) = s.split_once('\n') {
+            let Some((key, value)) = line.split_once(':') else {
+                break;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "image" => image = Some(value.parse::<Image>().map_err(|e| at_line(s, e))?),
+                "serves" | "servings" | "yield" => {
+                    let servings_str = value.split_whitespace().next().unwrap_or_default();
+                    let parsed = servings_str
+                        .parse::<u32>()
+                        .map_err(|_| at_line(s, ExpectedServings(key.clone())))?;
+                    servings = Some(parsed);
+                }
+                "tags" | "tag" | "categories" => add_tags(&mut tags, value.split(',')),
+                "difficulty" => {
+                    let parsed = value.parse::<Difficulty>().map_err(|e| at_line(s, e))?;
+                    difficulty = Some(parsed);
+                }
+                "rating" => {
+                    let parsed = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|r| (0.0..=5.0).contains(r))
+                        .ok_or_else(|| at_line(s, InvalidRating(value.to_string())))?;
+                    rating = Some(parsed);
+                }
+                "prep" | "prep time" | "cook" | "cook time" => {
+                    let duration = value
+                        .parse::<Duration>()
+                        .map_err(|e| at_line(s, InvalidDuration(key.clone(), e)))?;
+
+                    match key.starts_with("prep") {
+                        true => prep_time = Some(duration),
+                        false => cook_time = Some(duration),
+                    }
+                }
+                _ => break,
+            }
+
+            s = rest.trim_start();
+        }
+
+        let (introduction, s) = if !s.starts_with("---ingredients") {
+            // The introduction ends at a blank line, or right before `---ingredients`
+            let introduction_end = [s.find("\n\n"), s.find("\n---ingredients")]
+                .into_iter()
+                .flatten()
+                .min()
+                .ok_or_else(|| at_line(s, ExpectedIngredientsStart))?;
+            (
+                Some(s[..introduction_end].trim_start().to_string()),
+                s[introduction_end..].trim_start(),
+            )
+        } else {
+            (None, s)
+        };
+
+        if !s.starts_with("---ingredients") {
+            return Err(at_line(s, ExpectedIngredientsStart));
+        }
+
+        let mut s = s[14..].trim_start();
+        let mut sections = Vec::<IngredientSection>::new();
+        // Ingredients end at the first blank line, even one with spaces on it,
+        // or at the next `---` section if there's no blank line before it
+        while !s.starts_with("---") {
+            let (line, rest) = s
+                .split_once('\n')
+                .ok_or_else(|| at_line(s, UnexpectedEOF("Ingredient".into())))?;
+
+            if line.trim().is_empty() {
+                break;
+            }
+
+            add_ingredient_line(&mut sections, line).map_err(|e| at_line(s, e))?;
+            s = rest;
+        }
+        let ingredients = Ingredients { sections };
+
+        let mut s = s.trim_start();
+        let mut nutrition = None;
+
+        if let Some(rest) = s.strip_prefix("---nutrition") {
+            let mut facts = Nutrition::default();
+            s = rest.trim_start();
+
+            while let Some((line, rest)) = s.split_once('\n') {
+                if line.trim().is_empty() || line.starts_with("---") {
+                    break;
+                }
+
+                facts.parse_line(line).map_err(|e| at_line(s, e))?;
+                s = rest;
+            }
+
+            nutrition = Some(facts);
+            s = s.trim_start();
+        }
+
+        if !s.starts_with("---steps") {
+            return Err(at_line(s, ExpectedStepsStart));
+        }
+        let s = s[8..].trim();
+
+        // Either section can be empty, like the steps of a drink that's just
+        // poured together, which `validate` warns about rather than failing here.
+        // A comment on its own between steps leaves more than one blank line.
+        let steps = s
+            .split("\n\n")
+            .map(|step| step.trim_start_matches('\n'))
+            .filter(|step| !step.is_empty())
+            .map(Step::from)
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            title,
+            image,
+            introduction,
+            servings,
+            tags,
+            difficulty,
+            rating,
+            prep_time,
+            cook_time,
+            ingredients,
+            nutrition,
+            steps,
+        })
+    }
+}
+
+/// Puts a [`Recipe`] together in code, ingredient by ingredient and step by
+/// step. Ingredients go into the last section started with
+/// [`section`](Self::section), or an unnamed one before any.
+#[derive(Debug, Clone)]
+pub struct RecipeBuilder {
+    recipe: Recipe,
+}
+
+impl RecipeBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            recipe: Recipe {
+                title: title.into(),
+                image: None,
+                introduction: None,
+                servings: None,
+                tags: Vec::new(),
+                difficulty: None,
+                rating: None,
+                prep_time: None,
+                cook_time: None,
+                ingredients: Ingredients {
+                    sections: Vec::new(),
+                },
+                nutrition: None,
+                steps: Vec::new(),
+            },
+        }
+    }
+
+    pub fn introduction(mut self, introduction: impl Into<String>) -> Self {
+        self.recipe.introduction = Some(introduction.into());
+        self
+    }
+
+    pub fn servings(mut self, servings: u32) -> Self {
+        self.recipe.servings = Some(servings);
+        self
+    }
+
+    /// Adds a tag, lowercased and skipped if it's already there like when parsing
+    pub fn tag(mut self, tag: &str) -> Self {
+        add_tags(&mut self.recipe.tags, [tag]);
+        self
+    }
+
+    /// Starts a new section of ingredients, like `# Sauce` in a recipe file
+    pub fn section(mut self, name: impl Into<String>) -> Self {
+        self.recipe.ingredients.sections.push(IngredientSection {
+            name: name.into(),
+            ingredients: Vec::new(),
+            no_scale: false,
+        });
+        self
+    }
+
+    pub fn ingredient(mut self, ingredient: Ingredient) -> Self {
+        let sections = &mut self.recipe.ingredients.sections;
+        match sections.last_mut() {
+            Some(section) => section.ingredients.push(ingredient),
+            None => sections.push(IngredientSection {
+                name: String::new(),
+                ingredients: vec![ingredient],
+                no_scale: false,
+            }),
+        }
+        self
+    }
+
+    /// Adds a step, written like in the `---steps` section
+    pub fn step(mut self, step: &str) -> Self {
+        self.recipe.steps.push(Step::from(step));
+        self
+    }
+
+    pub fn build(self) -> Recipe {
+        self.recipe
+    }
+}
+
+/// Units a quantity can be written in when serializing, most preferred first.
+/// The first one is also used for amounts that aren't a whole number of any
+/// of them.
+const TXT_WEIGHT_UNITS: [(&str, u64); 3] = [
+    ("g", 1000),
+    ("lb", Weight::<Metric>::POUND),
+    ("oz", Weight::<Metric>::OUNCE),
+];
+const TXT_VOLUME_UNITS: [(&str, u64); 4] = [
+    ("ml", 1000),
+    ("cup", Volume::<Metric>::CUP),
+    ("tbsp", Volume::<Metric>::TBSP),
+    ("tsp", Volume::<Metric>::TSP),
+];
+
+/// Writes raw amounts in the first unit that fits all of them in exact quarters,
+/// so a range keeps one unit for both ends
+fn txt_amounts(raws: &[u64], units: &[(&'static str, u64)]) -> (Vec<String>, &'static str) {
+    let (unit, size) = units
+        .iter()
+        .find(|(_, size)| raws.iter().all(|r| r * 4 % size == 0))
+        .unwrap_or(&units[0]);
+    let amounts = raws.iter().map(|&r| (r as f64 / *size as f64).to_string());
+    let plural = raws.iter().any(|&r| r != *size);
+
+    match (*unit, plural) {
+        ("cup", true) => (amounts.collect(), "cups"),
+        _ => (amounts.collect(), unit),
+    }
+}
+
+impl<T: Copy> IngredientQuantity<T> {
+    /// Writes the quantity so that parsing it gives back the exact same amount
+    fn to_recipe_txt(&self) -> String {
+        use IngredientQuantity::*;
+
+        let (amounts, unit) = match self {
+            Weight(w) => txt_amounts(&[w.raw()], &TXT_WEIGHT_UNITS),
+            Volume(v) => txt_amounts(&[v.raw()], &TXT_VOLUME_UNITS),
+            Count(c) => return c.to_string(),
+            Range(l, h) => match (&**l, &**h) {
+                (Weight(l), Weight(h)) => txt_amounts(&[l.raw(), h.raw()], &TXT_WEIGHT_UNITS),
+                (Volume(l), Volume(h)) => txt_amounts(&[l.raw(), h.raw()], &TXT_VOLUME_UNITS),
+                (l, h) => return format!("{}-{}", l.to_recipe_txt(), h.to_recipe_txt()),
+            },
+        };
+
+        format!("{} {unit}", amounts.join("-"))
+    }
+}
+
+impl<T: Copy> Ingredient<T> {
+    /// Writes the ingredient as a line of the `---ingredients` section
+    pub fn to_recipe_txt(&self) -> String {
+        let mut line = match (&self.original, &self.quantity) {
+            (Some(original), Some(_)) => format!("{original} "),
+            (None, Some(q)) => format!("{} ", q.to_recipe_txt()),
+            (_, None) => String::new(),
+        };
+
+        match &self.sub_recipe {
+            Some(r) => line.push_str(&self.ingredient.replacen(
+                &r.name,
+                &format!("[[{}]]", r.name),
+                1,
+            )),
+            None => line.push_str(&self.ingredient),
+        }
+
+        for alternative in &self.alternatives {
+            line.push_str(&format!(" or {}", alternative.to_recipe_txt()));
+        }
+
+        if let Some(prep) = &self.prep {
+            line.push_str(&format!(", {prep}"));
+        }
+
+        if let Some(qualifier) = &self.qualifier {
+            line.push_str(&format!(", {qualifier}"));
+        }
+
+        if self.optional {
+            line.push_str(&format!(" {}", Self::OPTIONAL_MARKER));
+        }
+
+        if let Some(unit) = self.forced_unit {
+            line.push_str(&format!(" [{unit}]"));
+        }
+
+        line
+    }
+}
+
+impl<T: Copy> Ingredients<T> {
+    /// Writes the ingredients one per line, with a `# Heading` starting every
+    /// named section
+    pub fn to_recipe_txt(&self) -> String {
+        let mut txt = String::new();
+
+        for (i, section) in self.sections.iter().enumerate() {
+            match section.no_scale {
+                true if section.name.is_empty() => {
+                    txt.push_str(&format!("# {}\n", IngredientSection::<T>::NO_SCALE_MARKER))
+                }
+                true => txt.push_str(&format!(
+                    "# {} {}\n",
+                    section.name,
+                    IngredientSection::<T>::NO_SCALE_MARKER
+                )),
+                false if i > 0 || !section.name.is_empty() => {
+                    txt.push_str(&format!("# {}\n", section.name))
+                }
+                false => {}
+            }
+
+            for ingredient in &section.ingredients {
+                txt.push_str(&ingredient.to_recipe_txt());
+                txt.push('\n');
+            }
+        }
+
+        txt
+    }
+}
+
+impl FromStr for Ingredients {
+    type Err = RecipeError;
+
+    /// Parses ingredient lines like in the `---ingredients` section, skipping
+    /// blank lines
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sections = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if !line.is_empty() {
+                add_ingredient_line(&mut sections, line).map_err(|e| RecipeError::AtLine {
+                    line: i + 1,
+                    source: Box::new(e),
+                })?;
+            }
+        }
+
+        Ok(Ingredients { sections })
+    }
+}
+
+impl Nutrition {
+    /// Writes the known values as lines of the `---nutrition` section
+    fn to_recipe_txt(self) -> String {
+        [
+            ("calories", self.calories, " kcal"),
+            ("protein", self.protein, " g"),
+            ("fat", self.fat, " g"),
+            ("carbohydrates", self.carbohydrates, " g"),
+            ("fiber", self.fiber, " g"),
+            ("sugar", self.sugar, " g"),
+            ("sodium", self.sodium, " g"),
+        ]
+        .into_iter()
+        .filter_map(|(key, value, unit)| Some(format!("{key}: {}{unit}\n", value?)))
+        .collect()
+    }
+}
+
+impl<T: Copy> Recipe<T> {
+    /// Writes the recipe in the `.txt` format, so that parsing the result gives
+    /// back the same recipe. Quantities are written in grams or milliliters
+    /// unless they're a whole number of quarters of another unit, like cups.
+    pub fn to_recipe_txt(&self) -> String {
+        let mut txt = format!("{}\n\n", self.title);
+
+        if let Some(image) = &self.image {
+            match &image.alt {
+                Some(alt) => txt.push_str(&format!("image: {} | {alt}\n", image.href)),
+                None => txt.push_str(&format!("image: {}\n", image.href)),
+            }
+        }
+        if let Some(servings) = self.servings {
+            txt.push_str(&format!("servings: {servings}\n"));
+        }
+        if !self.tags.is_empty() {
+            txt.push_str(&format!("tags: {}\n", self.tags.join(", ")));
+        }
+        if let Some(difficulty) = self.difficulty {
+            txt.push_str(&format!("difficulty: {difficulty}\n"));
+        }
+        if let Some(rating) = self.rating {
+            txt.push_str(&format!("rating: {rating}\n"));
+        }
+        if let Some(prep) = self.prep_time {
+            txt.push_str(&format!("prep: {prep}\n"));
+        }
+        if let Some(cook) = self.cook_time {
+            txt.push_str(&format!("cook: {cook}\n"));
+        }
+
+        if let Some(introduction) = &self.introduction {
+            txt.push_str(&format!("\n{introduction}\n"));
+        }
+
+        txt.push_str(&format!(
+            "\n---ingredients\n\n{}\n",
+            self.ingredients.to_recipe_txt()
+        ));
+
+        if let Some(nutrition) = &self.nutrition {
+            txt.push_str(&format!("---nutrition\n\n{}\n", nutrition.to_recipe_txt()));
+        }
+
+        let steps = self
+            .steps
+            .iter()
+            .map(Step::to_recipe_txt)
+            .collect::<Vec<_>>();
+        txt.push_str(&format!("---steps\n\n{}\n", steps.join("\n\n")));
+
+        txt
+    }
+}
+
+/// Adds tags, lowercased, skipping empty ones and ones that are already there
+fn add_tags<'a>(tags: &mut Vec<String>, new: impl IntoIterator<Item = &'a str>) {
+    for tag in new.into_iter().map(|t| t.trim().to_lowercase()) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+}
+
+/// Whether a line is a comment, which [`Recipe::from_str`] skips wherever it
+/// is. Comments start with `//` or `#!`, the latter so they aren't taken for
+/// an ingredient section heading. They aren't kept, so
+/// [`Recipe::to_recipe_txt`] leaves them out.
+pub fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("//") || line.starts_with("#!")
+}
+
+/// Adds a line of the ingredient list, either an ingredient or a `# Section`
+/// heading, to `sections`
+fn add_ingredient_line(
+    sections: &mut Vec<IngredientSection>,
+    line: &str,
+) -> Result<(), RecipeError> {
+    if let Some(name) = line.strip_prefix('#') {
+        let name = name.trim();
+        let marker = name
+            .len()
+            .saturating_sub(<IngredientSection>::NO_SCALE_MARKER.len());
+        let (name, no_scale) = match name.get(marker..) {
+            Some(m) if m.eq_ignore_ascii_case(<IngredientSection>::NO_SCALE_MARKER) => {
+                (name[..marker].trim_end(), true)
+            }
+            _ => (name, false),
+        };
+
+        sections.push(IngredientSection {
+            name: name.to_string(),
+            ingredients: Vec::new(),
+            no_scale,
+        });
+        return Ok(());
+    }
+
+    let ingredient = line.parse::<Ingredient>()?;
+
+    match sections.last_mut() {
+        Some(section) => section.ingredients.push(ingredient),
+        None => sections.push(IngredientSection {
+            name: String::new(),
+            ingredients: vec![ingredient],
+            no_scale: false,
+        }),
+    }
+
+    Ok(())
+}
+
+/// The YAML front matter accepted by [`Recipe::from_yaml_frontmatter`]. Values
+/// are written like in the `.txt` format and parsed the same way.
+#[cfg(feature = "yaml")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatter {
+    title: String,
+    image: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    servings: Option<u32>,
+    difficulty: Option<String>,
+    rating: Option<f32>,
+    prep: Option<String>,
+    cook: Option<String>,
+    ingredients: Vec<String>,
+    nutrition: Option<Nutrition>,
+    steps: Vec<String>,
+}
+
+#[cfg(feature = "yaml")]
+impl Recipe {
+    /// Parses a recipe from a `---` delimited YAML block, followed by an
+    /// optional Markdown body that becomes the introduction:
+    ///
+    /// ```text
+    /// ---
+    /// title: Toast
+    /// tags: [breakfast, quick]
+    /// ingredients:
+    ///   - 2 slices bread
+    /// steps:
+    ///   - Toast the bread
+    /// ---
+    /// The simplest breakfast there is.
+    /// ```
+    pub fn from_yaml_frontmatter(s: &str) -> Result<Recipe, RecipeError> {
+        use RecipeError::*;
+
+        let s = s.trim_start().replace("\r\n", "\n");
+        let (yaml, body) = s
+            .strip_prefix("---\n")
+            .and_then(|s| s.split_once("\n---"))
+            .ok_or(ExpectedFrontMatter)?;
+        let body = body.split_once('\n').map_or("", |(_, b)| b).trim();
+
+        let front =
+            serde_yaml::from_str::<FrontMatter>(yaml).map_err(|e| CustomString(e.to_string()))?;
+
+        let duration = |key: &str, value: Option<String>| {
+            value
+                .map(|v| v.parse::<Duration>())
+                .transpose()
+                .map_err(|e| InvalidDuration(key.to_string(), e))
+        };
+
+        let mut tags = Vec::new();
+        add_tags(&mut tags, front.tags.iter().map(String::as_str));
+
+        let mut sections = Vec::new();
+        for line in &front.ingredients {
+            add_ingredient_line(&mut sections, line)?;
+        }
+
+        let rating = front
+            .rating
+            .map(|r| match (0.0..=5.0).contains(&r) {
+                true => Ok(r),
+                false => Err(InvalidRating(r.to_string())),
+            })
+            .transpose()?;
+
+        Ok(Recipe {
+            title: front.title,
+            image: front.image.map(|i| i.parse()).transpose()?,
+            introduction: (!body.is_empty()).then(|| body.to_string()),
+            servings: front.servings,
+            tags,
+            difficulty: front.difficulty.map(|d| d.parse()).transpose()?,
+            rating,
+            prep_time: duration("prep", front.prep)?,
+            cook_time: duration("cook", front.cook)?,
+            ingredients: Ingredients { sections },
+            nutrition: front.nutrition,
+            steps: front.steps.iter().map(|s| Step::from(s.as_str())).collect(),
+        })
+    }
+}
+
+/// Scores how well `query` fuzzily matches `text`, higher is better. Both are
+/// expected to be lowercase. Substring matches always beat subsequence matches,
+/// and `None` means the query isn't even a subsequence of the text.
+fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    if let Some(i) = text.find(query) {
+        return Some(2000 - i.min(999) as u32);
+    }
+
+    let mut chars = text.chars();
+    let mut gaps = 0u32;
+
+    for q in query.chars() {
+        loop {
+            if chars.next()? == q {
+                break;
+            }
+            gaps += 1;
+        }
+    }
+
+    Some(1000u32.saturating_sub(gaps))
+}
+
+/// The recipes bundled with the app, the same files the browser fetches
+#[cfg(feature = "ssr")]
+static RECIPE_DIR: include_dir::Dir<'static> = include_dir::include_dir!("./public/recipes");
+
+/// Parses a bundled recipe by name, like `egg_fried_rice`, straight from the
+/// files built into the binary. Unlike the app's fetching this doesn't need a
+/// browser, so it works in tests and tools too.
+#[cfg(feature = "ssr")]
+pub fn load_recipe_embedded(name: &str) -> Result<Recipe, RecipeError> {
+    let file = RECIPE_DIR
+        .get_file(format!("{name}.txt"))
+        .ok_or_else(|| RecipeError::NotFound(name.to_string()))?;
+
+    file.contents_utf8()
+        .ok_or_else(|| RecipeError::CustomString(format!("`{name}` isn't valid UTF-8")))?
+        .parse()
+}
+
+/// Reads and parses a recipe file from disk, for rendering outside the browser
+#[cfg(feature = "ssr")]
+pub fn load_recipe_file(path: impl AsRef<std::path::Path>) -> Result<Recipe, RecipeError> {
+    let path = path.as_ref();
+
+    match std::fs::read_to_string(path) {
+        Ok(s) => s.parse(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(RecipeError::NotFound(path.display().to_string()))
+        }
+        Err(e) => Err(RecipeError::CustomString(e.to_string())),
+    }
+}
+
+/// The items on the 1-based `page` of `items`, along with the number of pages.
+/// Pages past either end are clamped to the first or last page, and an empty
+/// list still has one, empty, page.
+pub fn paginate<T>(items: &[T], page: usize, page_size: usize) -> (&[T], usize) {
+    let page_size = page_size.max(1);
+    let pages = items.len().div_ceil(page_size).max(1);
+    let start = (page.clamp(1, pages) - 1) * page_size;
+    let end = (start + page_size).min(items.len());
+
+    (&items[start..end], pages)
+}
+
+/// Picks one of `names` based on `seed`, avoiding `previous` whenever there's
+/// anything else to pick so asking twice doesn't land on the same recipe.
+pub fn pick_recipe<'a>(names: &'a [String], seed: u64, previous: Option<&str>) -> Option<&'a str> {
+    if names.is_empty() {
+        return None;
+    }
+
+    // SplitMix64, so that nearby seeds still pick unrelated recipes
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+
+    let mut i = (z % names.len() as u64) as usize;
+
+    if names.len() > 1 && previous == Some(names[i].as_str()) {
+        i = (i + 1) % names.len();
+    }
+
+    Some(&names[i])
+}
+
+/// Orders for a list of recipes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RecipeSort {
+    /// Keeps the order the recipes are already in, which for the results of
+    /// [`search_recipes`] is best match first
+    Relevance,
+    #[default]
+    Title,
+    IngredientCount,
+    TotalTime,
+}
+
+impl RecipeSort {
+    pub const ALL: [RecipeSort; 4] = [
+        Self::Relevance,
+        Self::Title,
+        Self::IngredientCount,
+        Self::TotalTime,
+    ];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Relevance => "Relevance",
+            Self::Title => "Title",
+            Self::IngredientCount => "Number of ingredients",
+            Self::TotalTime => "Total time",
+        }
+    }
+
+    /// Sorts `recipes` in place. The sort is stable, so recipes that compare
+    /// equal keep their order, and ones without any times go last when sorting
+    /// by time.
+    pub fn sort<T>(self, recipes: &mut [&Recipe<T>]) {
+        self.sort_by_recipe(recipes, |r| *r);
+    }
+
+    /// Sorts indices into `recipes` like [`RecipeSort::sort`] sorts the
+    /// recipes they point at
+    pub fn sort_indices<T>(self, indices: &mut [usize], recipes: &[Recipe<T>]) {
+        self.sort_by_recipe(indices, |&i| &recipes[i]);
+    }
+
+    fn sort_by_recipe<'a, I, T: 'a>(self, items: &mut [I], recipe: impl Fn(&I) -> &'a Recipe<T>) {
+        match self {
+            Self::Relevance => {}
+            Self::Title => items.sort_by_cached_key(|i| recipe(i).title.to_lowercase()),
+            Self::IngredientCount => items.sort_by_key(|i| recipe(i).ingredients.len()),
+            Self::TotalTime => items.sort_by_key(|i| {
+                let time = recipe(i).total_time();
+                (time.is_none(), time)
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for RecipeSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Relevance => write!(f, "relevance"),
+            Self::Title => write!(f, "title"),
+            Self::IngredientCount => write!(f, "ingredients"),
+            Self::TotalTime => write!(f, "time"),
+        }
+    }
+}
+
+impl FromStr for RecipeSort {
+    type Err = RecipeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|sort| sort.to_string() == s)
+            .ok_or_else(|| RecipeError::CustomString(format!("Unknown sort order `{s}`")))
+    }
+}
+
+/// Finds recipes whose title or ingredients fuzzily match `query`, best match
+/// first. Title matches rank above ingredient matches. An empty or
+/// whitespace-only query returns every recipe in its original order.
+pub fn search_recipes<'a>(query: &str, recipes: &'a [Recipe]) -> Vec<&'a Recipe> {
+    search_recipe_indices(query, recipes)
+        .into_iter()
+        .map(|i| &recipes[i])
+        .collect()
+}
+
+/// Like [`search_recipes`], but gives the indices of the matching recipes in
+/// `recipes` instead of the recipes themselves
+pub fn search_recipe_indices(query: &str, recipes: &[Recipe]) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+
+    if query.is_empty() {
+        return (0..recipes.len()).collect();
+    }
+
+    let mut scored = recipes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| {
+            let title = fuzzy_score(&query, &r.title.to_lowercase()).map(|s| s * 2);
+            let ingredient = r
+                .ingredients
+                .iter()
+                .filter_map(|i| fuzzy_score(&query, &i.ingredient.to_lowercase()))
+                .max();
+
+            Some((title.max(ingredient)?, i))
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Combines the ingredients of several recipes into one list, merged like
+/// [`Recipe::consolidated_ingredients`]. Amounts are taken as they are, so when
+/// recipes are scaled with [`Recipe::scale`] first, their no-scale sections
+/// still only count once per recipe.
+pub fn shopping_list(recipes: &[Recipe]) -> Vec<Ingredient> {
+    merge_ingredients(recipes.iter().flat_map(|r| r.ingredients.iter()))
+}
+
+/// Which aisle of the shop ingredients are in, by their
+/// [canonical name](canonical_ingredient)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AisleTable(BTreeMap<String, String>);
+
+impl AisleTable {
+    /// The heading for ingredients that aren't in the table
+    pub const OTHER: &'static str = "Other";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, ingredient: &str, aisle: impl Into<String>) {
+        self.0
+            .insert(canonical_ingredient(ingredient), aisle.into());
+    }
+
+    pub fn get(&self, ingredient: &str) -> Option<&str> {
+        self.0
+            .get(&canonical_ingredient(ingredient))
+            .map(String::as_str)
+    }
+}
+
+/// The [`shopping_list`] as a plain text checklist to paste into a notes app,
+/// one `[ ] 400 g flour` line per ingredient under a line naming the recipes.
+/// With `aisles`, ingredients are grouped under a heading per aisle, sorted by
+/// name, with the ones not in the table last under [`AisleTable::OTHER`].
+pub fn shopping_list_text(
+    recipes: &[Recipe],
+    unit: UnitSystem,
+    aisles: Option<&AisleTable>,
+) -> String {
+    let titles = recipes.iter().map(|r| r.title.as_str()).collect::<Vec<_>>();
+    let mut txt = format!("Shopping list for {}\n", titles.join(", "));
+
+    let mut groups = BTreeMap::<(bool, &str), Vec<String>>::new();
+    for ingredient in shopping_list(recipes) {
+        let aisle = aisles.and_then(|a| a.get(&ingredient.ingredient));
+        // Ingredients without an aisle sort after every named one
+        let key = match aisle {
+            Some(aisle) => (false, aisle),
+            None => (true, AisleTable::OTHER),
+        };
+        groups
+            .entry(key)
+            .or_default()
+            .push(format!("[ ] {}\n", ingredient.to_string_in(unit)));
+    }
+
+    for ((_, aisle), lines) in groups {
+        txt.push('\n');
+        if aisles.is_some() {
+            txt.push_str(&format!("{aisle}\n"));
+        }
+        txt.extend(lines);
+    }
+
+    txt
+}
+
+/// Ingredients with the same [canonical name](canonical_ingredient) are summed
+/// when their quantities are of the same kind; anything that can't be summed
+/// gets its own entry. Repeated ingredients without a quantity, like `salt`,
+/// are only listed once.
+fn merge_ingredients<'a, T: Copy + 'a>(
+    ingredients: impl Iterator<Item = &'a Ingredient<T>>,
+) -> Vec<Ingredient<T>> {
+    let mut list = Vec::<Ingredient<T>>::new();
+
+    'ingredients: for ingredient in ingredients {
+        let key = canonical_ingredient(&ingredient.ingredient);
+        let same_name = list
+            .iter_mut()
+            .filter(|i| canonical_ingredient(&i.ingredient) == key);
+
+        for existing in same_name {
+            let merged = match (&existing.quantity, &ingredient.quantity) {
+                (None, None) => None,
+                (Some(a), Some(b)) => match a.checked_add(b) {
+                    Some(sum) => Some(sum),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            existing.quantity = merged;
+            existing.original = None;
+            existing.optional &= ingredient.optional;
+            continue 'ingredients;
+        }
+
+        list.push(ingredient.clone());
+    }
+
+    list
+}
+
+/// An amount of money in cents, in whatever currency the prices are in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Money(pub u64);
+
+impl std::ops::Add for Money {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Div<u32> for Money {
+    type Output = Self;
+
+    fn div(self, rhs: u32) -> Self::Output {
+        Money((self.0 as f64 / rhs as f64).round() as u64)
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+/// What an ingredient costs per canonical unit, so per kilogram, per liter or
+/// per item depending on how it's measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Price {
+    PerKg(Money),
+    PerLiter(Money),
+    PerItem(Money),
+}
+
+impl Price {
+    /// The cost of `quantity`, or `None` if it's measured in a different way
+    /// than the price. Ranges are costed at their high end.
+    fn cost<T: Copy>(self, quantity: &IngredientQuantity<T>) -> Option<Money> {
+        let (amount, per, price) = match (self, quantity) {
+            (_, IngredientQuantity::Range(_, high)) => return self.cost(high),
+            (Self::PerKg(p), IngredientQuantity::Weight(w)) => (w.raw() as f64, 1e6, p),
+            (Self::PerLiter(p), IngredientQuantity::Volume(v)) => (v.raw() as f64, 1e6, p),
+            (Self::PerItem(p), IngredientQuantity::Count(c)) => (*c, 1.0, p),
+            _ => return None,
+        };
+
+        Some(Money((amount / per * price.0 as f64).round() as u64))
+    }
+}
+
+/// Prices of ingredients by their [canonical name](canonical_ingredient)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PriceTable(BTreeMap<String, Price>);
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, ingredient: &str, price: Price) {
+        self.0.insert(canonical_ingredient(ingredient), price);
+    }
+
+    pub fn get(&self, ingredient: &str) -> Option<Price> {
+        self.0.get(&canonical_ingredient(ingredient)).copied()
+    }
+}
+
+/// The result of [`Recipe::estimated_cost`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    /// What the priced ingredients add up to
+    pub total: Money,
+    /// Ingredients left out of the total, because they aren't in the price
+    /// table or have no quantity to price
+    pub unpriced: Vec<String>,
+}
+
+impl<T: Copy> Recipe<T> {
+    /// Adds up what the ingredients cost, skipping the ones `prices` can't
+    /// price. When none of them could be priced the total is nothing and every
+    /// ingredient is unpriced.
+    pub fn estimated_cost(&self, prices: &PriceTable) -> CostEstimate {
+        let mut total = Money::default();
+        let mut unpriced = Vec::new();
+
+        for ingredient in self.ingredients.iter() {
+            let cost = prices
+                .get(&ingredient.ingredient)
+                .zip(ingredient.quantity.as_ref())
+                .and_then(|(price, quantity)| price.cost(quantity));
+
+            match cost {
+                Some(cost) => total = total + cost,
+                None => unpriced.push(ingredient.ingredient.clone()),
+            }
+        }
+
+        CostEstimate { total, unpriced }
+    }
+}
+
+/// Common allergens, as found in a recipe's ingredients by
+/// [`Recipe::allergens`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Nuts,
+    Eggs,
+    Shellfish,
+}
+
+impl Allergen {
+    pub const ALL: [Allergen; 5] = [
+        Self::Gluten,
+        Self::Dairy,
+        Self::Nuts,
+        Self::Eggs,
+        Self::Shellfish,
+    ];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Gluten => "Gluten",
+            Self::Dairy => "Dairy",
+            Self::Nuts => "Nuts",
+            Self::Eggs => "Eggs",
+            Self::Shellfish => "Shellfish",
+        }
+    }
+
+    /// The allergens in an ingredient, going by [`ALLERGEN_KEYWORDS`].
+    /// Ingredients without a keyword are taken to have none.
+    pub fn in_ingredient(name: &str) -> Vec<Allergen> {
+        let name = format!(" {} ", canonical_ingredient(name));
+        let found = ALLERGEN_KEYWORDS
+            .iter()
+            .filter(|(key, _)| name.contains(&format!(" {key} ")))
+            .collect::<Vec<_>>();
+
+        found
+            .iter()
+            .filter(|(key, _)| {
+                !found.iter().any(|(other, _)| {
+                    other.len() > key.len() && format!(" {other} ").contains(&format!(" {key} "))
+                })
+            })
+            .flat_map(|(_, allergens)| allergens.iter().copied())
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Allergen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Words in a [canonical ingredient name](canonical_ingredient) that give away
+/// an allergen. When keywords overlap only the longest counts, which is what
+/// the entries without allergens are for, so `coconut milk` isn't dairy and
+/// `peanut butter` is only nuts.
+pub const ALLERGEN_KEYWORDS: &[(&str, &[Allergen])] = &[
+    ("flour", &[Allergen::Gluten]),
+    ("wheat", &[Allergen::Gluten]),
+    ("bread", &[Allergen::Gluten]),
+    ("breadcrumb", &[Allergen::Gluten]),
+    ("pasta", &[Allergen::Gluten]),
+    ("spaghetti", &[Allergen::Gluten]),
+    ("noodle", &[Allergen::Gluten]),
+    ("couscous", &[Allergen::Gluten]),
+    ("barley", &[Allergen::Gluten]),
+    ("rye", &[Allergen::Gluten]),
+    ("rice flour", &[]),
+    ("almond flour", &[Allergen::Nuts]),
+    ("rice noodle", &[]),
+    ("milk", &[Allergen::Dairy]),
+    ("butter", &[Allergen::Dairy]),
+    ("buttermilk", &[Allergen::Dairy]),
+    ("cream", &[Allergen::Dairy]),
+    ("cheese", &[Allergen::Dairy]),
+    ("parmesan", &[Allergen::Dairy]),
+    ("mozzarella", &[Allergen::Dairy]),
+    ("yogurt", &[Allergen::Dairy]),
+    ("ghee", &[Allergen::Dairy]),
+    ("coconut milk", &[]),
+    ("coconut cream", &[]),
+    ("oat milk", &[]),
+    ("almond milk", &[Allergen::Nuts]),
+    ("cocoa butter", &[]),
+    ("peanut butter", &[Allergen::Nuts]),
+    ("almond", &[Allergen::Nuts]),
+    ("walnut", &[Allergen::Nuts]),
+    ("pecan", &[Allergen::Nuts]),
+    ("cashew", &[Allergen::Nuts]),
+    ("hazelnut", &[Allergen::Nuts]),
+    ("pistachio", &[Allergen::Nuts]),
+    ("peanut", &[Allergen::Nuts]),
+    ("egg", &[Allergen::Eggs]),
+    ("egg yolk", &[Allergen::Eggs]),
+    ("mayonnaise", &[Allergen::Eggs]),
+    ("shrimp", &[Allergen::Shellfish]),
+    ("prawn", &[Allergen::Shellfish]),
+    ("crab", &[Allergen::Shellfish]),
+    ("lobster", &[Allergen::Shellfish]),
+    ("mussel", &[Allergen::Shellfish]),
+    ("clam", &[Allergen::Shellfish]),
+    ("oyster", &[Allergen::Shellfish]),
+    ("scallop", &[Allergen::Shellfish]),
+];
+
+impl<T> Recipe<T> {
+    /// The allergens in any of the ingredients. Alternatives are left out,
+    /// since they're often there to swap one out.
+    pub fn allergens(&self) -> HashSet<Allergen> {
+        self.ingredients
+            .iter()
+            .flat_map(|i| Allergen::in_ingredient(&i.ingredient))
+            .collect()
+    }
+}
+
+/// How an ingredient or step differs between two versions of a recipe
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T> {
+    Added(T),
+    Removed(T),
+    Changed { before: T, after: T },
+}
+
+/// What changed between two versions of a recipe, as found by [`Recipe::diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeDiff<T = Metric> {
+    pub ingredients: Vec<Change<Ingredient<T>>>,
+    pub steps: Vec<Change<Step>>,
+}
+
+impl<T> RecipeDiff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.ingredients.is_empty() && self.steps.is_empty()
+    }
+}
+
+impl<T: Clone + PartialEq> Recipe<T> {
+    /// The ingredients and steps that differ in `other`. Ingredients are
+    /// matched up by [canonical name](canonical_ingredient), so a new amount is
+    /// a change rather than one ingredient removed and another added. Steps are
+    /// matched up in order, and steps replaced by as many others are changes.
+    pub fn diff(&self, other: &Recipe<T>) -> RecipeDiff<T> {
+        let mut unmatched = other.ingredients.iter().map(Some).collect::<Vec<_>>();
+        let mut ingredients = Vec::new();
+
+        for before in self.ingredients.iter() {
+            let name = canonical_ingredient(&before.ingredient);
+            let after = unmatched
+                .iter_mut()
+                .find(|a| a.is_some_and(|a| canonical_ingredient(&a.ingredient) == name))
+                .and_then(Option::take);
+
+            match after {
+                Some(after) if after == before => {}
+                Some(after) => ingredients.push(Change::Changed {
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                None => ingredients.push(Change::Removed(before.clone())),
+            }
+        }
+        ingredients.extend(unmatched.into_iter().flatten().cloned().map(Change::Added));
+
+        RecipeDiff {
+            ingredients,
+            steps: diff_steps(&self.steps, &other.steps),
+        }
+    }
+}
+
+/// Lines up two lists of steps by their longest common subsequence. Steps in
+/// between common ones pair off into changes, and the rest were added or
+/// removed.
+fn diff_steps(before: &[Step], after: &[Step]) -> Vec<Change<Step>> {
+    // `common[i][j]` is how many steps `before[i..]` and `after[j..]` share
+    let mut common = vec![vec![0; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = match before[i] == after[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            pair_changes(&mut changes, &mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || common[i][j + 1] >= common[i + 1][j]) {
+            added.push(after[j].clone());
+            j += 1;
+        } else {
+            removed.push(before[i].clone());
+            i += 1;
+        }
+    }
+    pair_changes(&mut changes, &mut removed, &mut added);
+
+    changes
+}
+
+/// Moves steps removed and added in the same place into `changes`, pairing
+/// them off as changed steps as far as they go
+fn pair_changes(changes: &mut Vec<Change<Step>>, removed: &mut Vec<Step>, added: &mut Vec<Step>) {
+    let paired = removed.len().min(added.len());
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+
+    for (before, after) in removed.by_ref().zip(added.by_ref()).take(paired) {
+        changes.push(Change::Changed { before, after });
+    }
+    changes.extend(removed.map(Change::Removed));
+    changes.extend(added.map(Change::Added));
+}
+
+/// Maps tags, ingredients and titles to the names of the recipes containing them,
+/// so lookups don't need to re-parse every recipe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipeIndex {
+    tags: BTreeMap<String, Vec<String>>,
+    ingredients: BTreeMap<String, Vec<String>>,
+    titles: BTreeMap<String, Vec<String>>,
+}
+
+impl RecipeIndex {
+    /// Builds an index from recipes paired with their names
+    pub fn new(recipes: &[(String, Recipe)]) -> Self {
+        let mut index = Self::default();
+
+        for (name, recipe) in recipes {
+            index.insert(name, recipe);
+        }
+
+        index
+    }
+
+    /// Adds a recipe to the index under `name`
+    pub fn insert(&mut self, name: &str, recipe: &Recipe) {
+        for tag in &recipe.tags {
+            insert_name(&mut self.tags, tag.to_lowercase(), name);
+        }
+        for ingredient in recipe.ingredients.iter() {
+            insert_name(
+                &mut self.ingredients,
+                canonical_ingredient(&ingredient.ingredient),
+                name,
+            );
+        }
+        insert_name(&mut self.titles, recipe.title.to_lowercase(), name);
+    }
+
+    /// Every tag in the index, sorted
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.keys().map(String::as_str)
+    }
+
+    /// Recipes carrying exactly `tag`, ignoring case
+    pub fn by_tag(&self, tag: &str) -> &[String] {
+        self.tags
+            .get(&tag.trim().to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Recipes with an ingredient whose name contains `query`, ignoring case
+    /// and plurals
+    pub fn by_ingredient(&self, query: &str) -> Vec<&str> {
+        lookup_containing(&self.ingredients, &canonical_ingredient(query))
+    }
+
+    /// Recipes whose title contains `query`, ignoring case
+    pub fn by_title(&self, query: &str) -> Vec<&str> {
+        lookup_containing(&self.titles, query)
+    }
+
+    /// The name of the recipe a reference points to, matching either the
+    /// recipe's file name or its full title
+    pub fn resolve(&self, r: &SubRecipeRef) -> Option<&str> {
+        let slug = r.slug();
+
+        self.titles
+            .values()
+            .flatten()
+            .find(|name| name.eq_ignore_ascii_case(&slug))
+            .or_else(|| self.titles.get(&r.name.to_lowercase())?.first())
+            .map(String::as_str)
+    }
+}
+
+/// A [`RecipeIndex`] built a chunk of recipes at a time, so a large collection
+/// can be parsed in between renders instead of all at once before the first.
+/// Like [`RecipeIndex::new`], recipes that don't parse are left out, but their
+/// errors are kept in [`IncrementalIndex::failures`].
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalIndex<'a> {
+    sources: Vec<(&'a str, &'a str)>,
+    parsed: usize,
+    recipes: Vec<(String, Recipe)>,
+    failures: Vec<(String, RecipeError)>,
+    index: RecipeIndex,
+}
+
+impl<'a> IncrementalIndex<'a> {
+    /// Starts on recipe sources paired with their names, without parsing any
+    pub fn new(sources: Vec<(&'a str, &'a str)>) -> Self {
+        Self {
+            sources,
+            ..Self::default()
+        }
+    }
+
+    /// Parses and indexes up to `n` more recipes, returning whether that was
+    /// the last of them
+    pub fn index_chunk(&mut self, n: usize) -> bool {
+        let end = self.parsed.saturating_add(n).min(self.sources.len());
+
+        for &(name, source) in &self.sources[self.parsed..end] {
+            match source.parse::<Recipe>() {
+                Ok(recipe) => {
+                    self.index.insert(name, &recipe);
+                    self.recipes.push((name.to_string(), recipe));
+                }
+                Err(e) => self.failures.push((name.to_string(), e)),
+            }
+        }
+        self.parsed = end;
+
+        self.is_complete()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.parsed == self.sources.len()
+    }
+
+    /// How many of the sources have been parsed, out of how many there are
+    pub fn progress(&self) -> (usize, usize) {
+        (self.parsed, self.sources.len())
+    }
+
+    /// The recipes parsed so far, in the order of their sources
+    pub fn recipes(&self) -> &[(String, Recipe)] {
+        &self.recipes
+    }
+
+    /// The names of the recipes that failed to parse so far, with why
+    pub fn failures(&self) -> &[(String, RecipeError)] {
+        &self.failures
+    }
+
+    /// The index of the recipes parsed so far
+    pub fn index(&self) -> &RecipeIndex {
+        &self.index
+    }
+
+    /// Parses whatever is left, returning every recipe and the full index
+    pub fn finish(mut self) -> (Vec<(String, Recipe)>, RecipeIndex) {
+        self.index_chunk(usize::MAX);
+        (self.recipes, self.index)
+    }
+}
+
+fn insert_name(map: &mut BTreeMap<String, Vec<String>>, key: String, name: &str) {
+    let names = map.entry(key).or_default();
+
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_string());
+    }
+}
+
+fn lookup_containing<'a>(map: &'a BTreeMap<String, Vec<String>>, query: &str) -> Vec<&'a str> {
+    let query = query.trim().to_lowercase();
+    let mut names = map
+        .iter()
+        .filter(|(key, _)| key.contains(&query))
+        .flat_map(|(_, names)| names.iter().map(String::as_str))
+        .collect::<Vec<_>>();
+
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Escapes the characters that are meaningful in HTML, so arbitrary text can be
+/// placed inside an element or attribute.
+#[cfg(feature = "markdown")]
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Only links to the web, mail addresses or paths on this site are rendered, so
+/// `javascript:` and friends can't sneak in through a recipe.
+#[cfg(feature = "markdown")]
+fn is_safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+
+    ["http://", "https://", "mailto:", "/", "#"]
+        .iter()
+        .any(|p| lower.starts_with(p))
+        || !lower.contains(':')
+}
+
+/// Like in CommonMark, `*` only emphasizes text it hugs, so `2 * 3 * 4` stays as is.
+#[cfg(feature = "markdown")]
+fn is_emphasis(inner: &str) -> bool {
+    !inner.is_empty() && inner.trim() == inner
+}
+
+#[cfg(feature = "markdown")]
+fn render_inline(s: &str, html: &mut String, route: &dyn Fn(&SubRecipeRef) -> String) {
+    let mut rest = s;
+
+    while let Some(i) = rest.find(['*', '[']) {
+        html.push_str(&escape_html(&rest[..i]));
+        let tail = &rest[i..];
+
+        let rendered = if tail.starts_with("[[") {
+            SubRecipeRef::parse_leading(tail).map(|(r, after)| {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(&join_path(BASE_PATH, &route(&r))),
+                    escape_html(&r.name)
+                ));
+                after
+            })
+        } else if let Some(inner) = tail.strip_prefix("**") {
+            inner
+                .find("**")
+                .filter(|&end| is_emphasis(&inner[..end]))
+                .map(|end| {
+                    html.push_str("<strong>");
+                    render_inline(&inner[..end], html, route);
+                    html.push_str("</strong>");
+                    &inner[end + 2..]
+                })
+        } else if let Some(inner) = tail.strip_prefix('*') {
+            inner
+                .find('*')
+                .filter(|&end| is_emphasis(&inner[..end]))
+                .map(|end| {
+                    html.push_str("<em>");
+                    render_inline(&inner[..end], html, route);
+                    html.push_str("</em>");
+                    &inner[end + 1..]
+                })
+        } else {
+            // Only a `]` directly followed by `(` makes a link, anything else
+            // in brackets is plain text
+            tail[1..].split_once(']').and_then(|(text, after)| {
+                let (url, after) = after.strip_prefix('(')?.split_once(')')?;
+
+                if is_safe_url(url) {
+                    html.push_str(&format!("<a href=\"{}\">", escape_html(url.trim())));
+                    render_inline(text, html, route);
+                    html.push_str("</a>");
+                } else {
+                    render_inline(text, html, route);
+                }
+
+                Some(after)
+            })
+        };
+
+        match rendered {
+            Some(after) => rest = after,
+            None => {
+                html.push_str(&escape_html(&tail[..1]));
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    html.push_str(&escape_html(rest));
+}
+
+/// Links mentions of `ingredients` in HTML from [`markdown_to_html`] to their
+/// entry in the ingredient list, `#ingredient-{index}`. Text that's already
+/// inside a link is left alone.
+#[cfg(feature = "markdown")]
+pub fn link_ingredients(html: &str, ingredients: &[&str], class: &str) -> String {
+    let mut linked = String::with_capacity(html.len());
+    let mut in_link = false;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let (text, after) = rest.split_at(rest.find('<').unwrap_or(rest.len()));
+
+        let mut last = 0;
+        let mentions = match in_link {
+            true => Vec::new(),
+            false => ingredient_mentions(text, ingredients),
+        };
+        for (range, index) in mentions {
+            linked.push_str(&text[last..range.start]);
+            linked.push_str(&format!(
+                "<a class=\"{class}\" href=\"#ingredient-{index}\">{}</a>",
+                &text[range.clone()]
+            ));
+            last = range.end;
+        }
+        linked.push_str(&text[last..]);
+
+        let (tag, after) = after.split_at(after.find('>').map_or(after.len(), |i| i + 1));
+        if tag.starts_with("<a ") || tag == "<a>" {
+            in_link = true;
+        } else if tag == "</a>" {
+            in_link = false;
+        }
+        linked.push_str(tag);
+        rest = after;
+    }
+
+    linked
+}
+
+/// Renders the inline Markdown used in steps and introductions, `**bold**`,
+/// `*italic*` and `[text](url)`, to HTML. References to other recipes,
+/// `[[name]]`, become links too. Everything else is escaped, so the output is
+/// safe to use as inner HTML.
+#[cfg(feature = "markdown")]
+pub fn markdown_to_html(s: &str) -> String {
+    markdown_to_html_with(s, SubRecipeRef::href)
+}
+
+/// Like [`markdown_to_html`], but references to other recipes link to the
+/// route `route` gives them, like one [resolved](RecipeIndex::resolve) against
+/// the recipes there are
+#[cfg(feature = "markdown")]
+pub fn markdown_to_html_with(s: &str, route: impl Fn(&SubRecipeRef) -> String) -> String {
+    let mut html = String::with_capacity(s.len());
+    render_inline(s, &mut html, &route);
+    html
+}
+
 #[test]
 fn measurements() {
     // let mut m = Weight::new::<gram>(1040.0);
@@ -319,8 +3914,2275 @@ fn measurements() {
     // m = m.round::<pound>();
     // m /= 10.0;
 
-    // println!(
-    //     "{}",
-    //     m.into_format_args(pound, uom::fmt::DisplayStyle::Abbreviation)
-    // );
+    // println!(
+    //     "{}",
+    //     m.into_format_args(pound, uom::fmt::DisplayStyle::Abbreviation)
+    // );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_count() {
+        let eggs = "8 eggs".parse::<Ingredient>().unwrap();
+
+        assert_eq!(eggs.ingredient, "eggs");
+        assert_eq!(eggs.quantity, Some(IngredientQuantity::Count(8.0)));
+        assert_eq!(eggs.to_string(), "8 eggs");
+        assert_eq!(eggs.clone().as_imperial().to_string(), "8 eggs");
+        assert_eq!(eggs.as_imperial().as_metric().to_string(), "8 eggs");
+
+        let salt = "salt".parse::<Ingredient>().unwrap();
+
+        assert_eq!(salt.quantity, None);
+    }
+
+    #[test]
+    fn scale_recipe() {
+        let s = "Bread\n\n---ingredients\n200 g flour\n2 eggs\nsalt\n\n---steps\nBake it";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let doubled = recipe.scale(2.0, Rounding::Exact);
+
+        let ingredients = doubled.ingredients.iter().collect::<Vec<_>>();
+
+        assert_eq!(ingredients[0].to_string(), "400 g flour");
+        assert_eq!(ingredients[1].to_string(), "4 eggs");
+        assert_eq!(ingredients[2].quantity, None);
+        assert_eq!(doubled.title, recipe.title);
+        assert_eq!(doubled.steps, recipe.steps);
+        assert_eq!(
+            recipe
+                .scale(0.5, Rounding::Exact)
+                .ingredients
+                .iter()
+                .next()
+                .unwrap()
+                .to_string(),
+            "100 g flour"
+        );
+    }
+
+    #[test]
+    fn parse_sections() {
+        let s = "Pizza\n\n---ingredients\n2 eggs\n# Dough\n500 g flour\n# Sauce\n400 mL tomatoes\nsalt\n\n---steps\nBake it";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let sections = &recipe.ingredients.sections;
+
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].name, "");
+        assert_eq!(sections[0].ingredients.len(), 1);
+        assert_eq!(sections[1].name, "Dough");
+        assert_eq!(sections[1].ingredients[0].to_string(), "500 g flour");
+        assert_eq!(sections[2].name, "Sauce");
+        assert_eq!(sections[2].ingredients.len(), 2);
+        assert_eq!(recipe.ingredients.len(), 4);
+    }
+
+    #[test]
+    fn parse_leading_section() {
+        let s = "Pizza\n\n---ingredients\n# Dough\n500 g flour\n\n---steps\nBake it";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(recipe.ingredients.sections.len(), 1);
+        assert_eq!(recipe.ingredients.sections[0].name, "Dough");
+    }
+
+    #[test]
+    fn parse_servings() {
+        let serves = "Rice\n\nserves: 4\n\n---ingredients\n1 rice cup rice\n\n---steps\nCook it";
+        let yields = "Cookies\n\nimage: cookies.png\nyield: 12 cookies\n\nSo chewy\n\n---ingredients\n200 g flour\n\n---steps\nBake it";
+        let missing = "Rice\n\nserves:\n\n---ingredients\n1 rice cup rice\n\n---steps\nCook it";
+
+        assert_eq!(serves.parse::<Recipe>().unwrap().servings, Some(4));
+
+        let cookies = yields.parse::<Recipe>().unwrap();
+        assert_eq!(cookies.servings, Some(12));
+        assert_eq!(cookies.image.unwrap().href, "cookies.png");
+        assert_eq!(cookies.introduction.as_deref(), Some("So chewy"));
+
+        assert!(matches!(
+            missing.parse::<Recipe>().unwrap_err().inner(),
+            RecipeError::ExpectedServings(key) if key == "serves"
+        ));
+        assert_eq!(
+            "Rice\n\n---ingredients\n1 egg\n\n---steps\nCook it"
+                .parse::<Recipe>()
+                .unwrap()
+                .servings,
+            None
+        );
+    }
+
+    #[test]
+    fn parse_times() {
+        let s = "Stew\n\nprep: 20m\ncook: 1h 30m\n\n---ingredients\n1 onion\n\n---steps\nSimmer";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(recipe.prep_time, Some(Duration::from_minutes(20)));
+        assert_eq!(recipe.cook_time, Some(Duration::from_minutes(90)));
+
+        let malformed = "Stew\n\nprep: abc\n\n---ingredients\n1 onion\n\n---steps\nSimmer";
+
+        assert!(matches!(
+            malformed.parse::<Recipe>().unwrap_err().inner(),
+            RecipeError::InvalidDuration(key, MeasurementError::InvalidFormat) if key == "prep"
+        ));
+    }
+
+    #[test]
+    fn error_line_numbers() {
+        let missing_steps = "Bread\n\nA simple loaf\n\n---ingredients\n200 g flour\n\nBake it";
+        let err = missing_steps.parse::<Recipe>().unwrap_err();
+
+        assert_eq!(err.line(), Some(8));
+        assert!(matches!(err.inner(), RecipeError::ExpectedStepsStart));
+        assert!(err.to_string().starts_with("Line 8: "));
+
+        let bad_prep =
+            "Bread\n\nserves: 2\nprep: soon\n\n---ingredients\n200 g flour\n\n---steps\nBake";
+
+        assert_eq!(bad_prep.parse::<Recipe>().unwrap_err().line(), Some(4));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let s = include_str!("../public/recipes/egg_fried_rice.txt");
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        let json = recipe.to_json();
+        let parsed = Recipe::from_json(&json).unwrap();
+
+        assert_eq!(parsed, recipe);
+        assert!(Recipe::from_json("{\"title\": 1}").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_quantities_are_plain_numbers() {
+        let ingredient = "200 g flour".parse::<Ingredient>().unwrap();
+        let json = serde_json::to_string(&ingredient).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"ingredient":"flour","prep":null,"quantity":{"Weight":200000},"original":"200 g","optional":false}"#
+        );
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let cups = "2-3 cups flour".parse::<Ingredient>().unwrap();
+        let garlic = "2 to 3 cloves garlic".parse::<Ingredient>().unwrap();
+        let soy = "1-2 tbsp soy sauce".parse::<Ingredient>().unwrap();
+
+        assert_eq!(cups.ingredient, "flour");
+        assert_eq!(
+            cups.quantity,
+            Some(IngredientQuantity::Range(
+                Box::new(IngredientQuantity::Volume(Volume::new_metric(
+                    2 * Volume::<Metric>::CUP
+                ))),
+                Box::new(IngredientQuantity::Volume(Volume::new_metric(
+                    3 * Volume::<Metric>::CUP
+                ))),
+            ))
+        );
+        assert_eq!(cups.clone().as_imperial().to_string(), "2-3 cups flour");
+        assert_eq!(cups.to_string(), "0.5-0.7 l flour");
+
+        assert_eq!(garlic.ingredient, "cloves garlic");
+        assert_eq!(garlic.to_string(), "2-3 cloves garlic");
+        assert_eq!(garlic.scale(2.0).to_string(), "4-6 cloves garlic");
+
+        assert_eq!(soy.as_imperial().to_string(), "0.5-1.0 floz soy sauce");
+    }
+
+    #[test]
+    fn ranges_across_units() {
+        let display = |s: &str, unit| s.parse::<Ingredient>().unwrap().to_string_in(unit);
+
+        assert_eq!(
+            display("2-3 cups milk", UnitSystem::Metric),
+            "0.5-0.7 l milk"
+        );
+        assert_eq!(
+            display("5-10 ml salt", UnitSystem::Imperial),
+            "1/3-1/2 tbsp salt"
+        );
+        assert_eq!(
+            display("1-3 lb flour", UnitSystem::Metric),
+            "0.5-1.4 kg flour"
+        );
+        assert_eq!(
+            display("1 pinch-1/4 tsp salt", UnitSystem::Imperial),
+            "1 pinch-1/4 tsp salt"
+        );
+    }
+
+    #[test]
+    fn parse_non_ranges() {
+        let negative = "-5 g butter".parse::<Ingredient>().unwrap();
+        let hyphenated = "2 sun-dried tomatoes".parse::<Ingredient>().unwrap();
+
+        assert!(!matches!(
+            negative.quantity,
+            Some(IngredientQuantity::Range(..))
+        ));
+        assert_eq!(hyphenated.quantity, Some(IngredientQuantity::Count(2.0)));
+        assert_eq!(hyphenated.ingredient, "sun-dried tomatoes");
+    }
+
+    #[test]
+    fn parse_optional() {
+        let chili = "1 tsp chili flakes (optional)"
+            .parse::<Ingredient>()
+            .unwrap();
+        let onion = "1 onion (finely chopped)".parse::<Ingredient>().unwrap();
+        let cream = "cream (Optional)".parse::<Ingredient>().unwrap();
+
+        assert!(chili.optional);
+        assert_eq!(chili.ingredient, "chili flakes");
+        assert_eq!(chili.to_string(), "5 ml chili flakes (optional)");
+
+        assert!(!onion.optional);
+        assert_eq!(onion.ingredient, "onion (finely chopped)");
+
+        assert!(cream.optional);
+        assert_eq!(cream.ingredient, "cream");
+        assert_eq!(cream.quantity, None);
+    }
+
+    #[test]
+    fn search() {
+        let rice = include_str!("../public/recipes/egg_fried_rice.txt")
+            .parse::<Recipe>()
+            .unwrap();
+        let bread = "Bread\n\n---ingredients\n500 g flour\nsalt\n\n---steps\nBake"
+            .parse::<Recipe>()
+            .unwrap();
+        let recipes = [rice, bread];
+
+        let titles = |query: &str| {
+            search_recipes(query, &recipes)
+                .into_iter()
+                .map(|r| r.title.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(titles("BREAD"), ["Bread"]);
+        assert_eq!(titles("leeks"), ["Egg Fried Rice"]);
+        assert_eq!(titles("efr"), ["Egg Fried Rice"]);
+        assert_eq!(titles("salt"), ["Egg Fried Rice", "Bread"]);
+        assert_eq!(titles("rd"), ["Bread", "Egg Fried Rice"]);
+        assert_eq!(titles("   "), ["Egg Fried Rice", "Bread"]);
+        assert_eq!(titles(""), ["Egg Fried Rice", "Bread"]);
+        assert!(titles("zzz").is_empty());
+
+        assert_eq!(search_recipe_indices("rd", &recipes), [1, 0]);
+        assert_eq!(search_recipe_indices("", &recipes), [0, 1]);
+    }
+
+    #[test]
+    fn combine_shopping_list() {
+        let bread =
+            "Bread\n\n---ingredients\n200 g flour\n1 tsp salt\n2 eggs\nwater\n\n---steps\nBake"
+                .parse::<Recipe>()
+                .unwrap();
+        let cake = "Cake\n\n---ingredients\n200 g Flour\n1 cup flour\n3 eggs\nwater\n1-2 tsp salt\n\n---steps\nBake"
+            .parse::<Recipe>()
+            .unwrap();
+
+        let list = shopping_list(&[bread, cake])
+            .into_iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            list,
+            [
+                "400 g flour",
+                "5 ml salt",
+                "5 eggs",
+                "water",
+                "237 ml flour",
+                "5-10 ml salt",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_image_alt() {
+        let parse = |image: &str| {
+            format!("Rice\n\nimage: {image}\n\n---ingredients\n1 egg\n\n---steps\nCook")
+                .parse::<Recipe>()
+                .map(|r| r.image.unwrap())
+        };
+
+        let with_alt = parse("rice.jpg | A bowl of fried rice").unwrap();
+        assert_eq!(with_alt.href, "rice.jpg");
+        assert_eq!(with_alt.alt.as_deref(), Some("A bowl of fried rice"));
+
+        let without_alt = parse("rice.jpg").unwrap();
+        assert_eq!(without_alt.href, "rice.jpg");
+        assert_eq!(without_alt.alt, None);
+
+        assert_eq!(parse("rice.jpg |").unwrap().alt, None);
+        assert!(matches!(
+            parse("| Just alt text").unwrap_err().inner(),
+            RecipeError::ExpectedImageHref
+        ));
+    }
+
+    #[test]
+    fn convert_ingredient_density() {
+        let water = "500 g water".parse::<Ingredient>().unwrap();
+        let flour = "1 cup flour".parse::<Ingredient>().unwrap();
+
+        assert_eq!(
+            water.convert_with_density().unwrap().to_string(),
+            "0.5 l water"
+        );
+        assert_eq!(
+            flour.convert_with_density().unwrap().to_string(),
+            "125 g flour"
+        );
+        assert!("2 eggs"
+            .parse::<Ingredient>()
+            .unwrap()
+            .convert_with_density()
+            .is_none());
+        assert!("salt"
+            .parse::<Ingredient>()
+            .unwrap()
+            .convert_with_density()
+            .is_none());
+        assert!("100 g cheese"
+            .parse::<Ingredient>()
+            .unwrap()
+            .convert_with_density()
+            .is_none());
+    }
+
+    #[test]
+    fn ingredient_in_unit_system() {
+        let milk = "1 quart milk".parse::<Ingredient>().unwrap();
+
+        assert_eq!(milk.to_string_in(UnitSystem::Metric), "0.9 l milk");
+        assert_eq!(milk.to_string_in(UnitSystem::Imperial), "1 quart milk");
+        assert_eq!(milk.to_string_in(UnitSystem::ImperialUk), "1.7 pints milk");
+    }
+
+    #[test]
+    fn parse_pinch_ingredient() {
+        let salt = "a pinch of salt".parse::<Ingredient>().unwrap();
+
+        assert_eq!(
+            salt.quantity,
+            Some(IngredientQuantity::Volume(Volume::new_metric(
+                Volume::<Metric>::PINCH
+            )))
+        );
+        assert_eq!(salt.to_string_in(UnitSystem::Imperial), "a pinch of salt");
+        assert_eq!(
+            salt.scale(1.0).to_string_in(UnitSystem::Metric),
+            "a pinch of salt"
+        );
+        assert_eq!(
+            salt.scale(2.0).to_string_in(UnitSystem::Imperial),
+            "1/8 tsp of salt"
+        );
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn markdown() {
+        assert_eq!(
+            markdown_to_html("Add the **salt** and stir"),
+            "Add the <strong>salt</strong> and stir"
+        );
+        assert_eq!(
+            markdown_to_html("*gently* fold, see [folding](https://example.com/fold)"),
+            "<em>gently</em> fold, see <a href=\"https://example.com/fold\">folding</a>"
+        );
+        assert_eq!(
+            markdown_to_html("Use the [large] pan, see [folding](/fold)"),
+            "Use the [large] pan, see <a href=\"/fold\">folding</a>"
+        );
+        assert_eq!(
+            markdown_to_html("Add [1] egg (beaten)"),
+            "Add [1] egg (beaten)"
+        );
+        assert_eq!(
+            markdown_to_html("<script>alert('hi')</script>"),
+            "&lt;script&gt;alert(&#39;hi&#39;)&lt;/script&gt;"
+        );
+        assert_eq!(markdown_to_html("[click](javascript:alert(1))"), "click)");
+        assert_eq!(markdown_to_html("2 * 3 and **"), "2 * 3 and **");
+    }
+
+    #[test]
+    fn parse_tags() {
+        let s = "Soup\n\ntags: Vegan, quick,, vegan , dinner,\n\n---ingredients\n1 onion\n\n---steps\nCook";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(recipe.tags, vec!["vegan", "quick", "dinner"]);
+
+        let s = "Soup\n\n---ingredients\n1 onion\n\n---steps\nCook";
+        assert!(s.parse::<Recipe>().unwrap().tags.is_empty());
+    }
+
+    #[test]
+    fn recipe_index() {
+        let recipes = [
+            (
+                "egg_fried_rice".to_string(),
+                include_str!("../public/recipes/egg_fried_rice.txt")
+                    .parse::<Recipe>()
+                    .unwrap(),
+            ),
+            (
+                "boscaiola".to_string(),
+                include_str!("../public/recipes/boscaiola.txt")
+                    .parse::<Recipe>()
+                    .unwrap(),
+            ),
+        ];
+        let index = RecipeIndex::new(&recipes);
+
+        assert_eq!(index.by_ingredient("egg"), vec!["egg_fried_rice"]);
+        assert_eq!(index.by_ingredient("EGG"), vec!["egg_fried_rice"]);
+        assert!(index.by_ingredient("chocolate").is_empty());
+        assert_eq!(index.by_tag("dinner"), ["egg_fried_rice", "boscaiola"]);
+        assert_eq!(index.by_tag("Pasta"), ["boscaiola"]);
+        assert!(index.by_tag("dessert").is_empty());
+        assert_eq!(index.by_title("fried"), vec!["egg_fried_rice"]);
+        assert!(index.tags().any(|t| t == "quick"));
+    }
+
+    #[test]
+    fn parse_fraction_ingredients() {
+        let sugar = "1 1/2 cups sugar".parse::<Ingredient>().unwrap();
+        let onion = "1/2 onion".parse::<Ingredient>().unwrap();
+        let salt = "1/2-1 tsp salt".parse::<Ingredient>().unwrap();
+
+        assert_eq!(sugar.ingredient, "sugar");
+        assert_eq!(sugar.as_imperial().to_string(), "1 1/2 cups sugar");
+        assert_eq!(onion.ingredient, "onion");
+        assert_eq!(onion.quantity, Some(IngredientQuantity::Count(0.5)));
+        assert_eq!(salt.ingredient, "salt");
+        assert!(matches!(salt.quantity, Some(IngredientQuantity::Range(..))));
+    }
+
+    #[test]
+    fn step_durations() {
+        let step = Step::from;
+
+        assert_eq!(
+            step("Simmer for 20 minutes.").durations(),
+            vec![Duration::from_minutes(20)]
+        );
+        assert_eq!(
+            step("Rest 1 hour 30 minutes, then bake for 45m and cool (10 mins)").durations(),
+            vec![
+                Duration::from_minutes(90),
+                Duration::from_minutes(45),
+                Duration::from_minutes(10)
+            ]
+        );
+        assert!(step("Beat 2 eggs into 200 g flour").durations().is_empty());
+    }
+
+    #[test]
+    fn validate_unused_ingredient() {
+        let s = "Toast\n\nimage: toast.png\n\n---ingredients\n2 slices bread\n10 g butter\n1 pinch salt\n\n---steps\nToast the bread.\n\nSpread the butter on top.";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(
+            recipe.validate(),
+            vec![RecipeWarning::UnusedIngredient("salt".to_string())]
+        );
+    }
+
+    #[test]
+    fn validate_empty_steps() {
+        let s = "Nothing\n\nimage: picture link\n\n---ingredients\n1 egg\n\n---steps\n";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let warnings = recipe.validate();
+
+        assert!(warnings.contains(&RecipeWarning::NoSteps));
+        assert!(warnings.contains(&RecipeWarning::InvalidImageHref("picture link".to_string())));
+        assert_eq!(
+            RecipeWarning::NoSteps.to_string(),
+            "The recipe has no steps"
+        );
+    }
+
+    #[test]
+    fn validate_unlisted_ingredient() {
+        let s = "Eggs\n\n---ingredients\n2 eggs\n\n---steps\nBoil the eggs.\n\nServe with butter.";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(
+            recipe.validate(),
+            vec![RecipeWarning::UnlistedIngredient {
+                step: 2,
+                ingredient: "butter".to_string()
+            }]
+        );
+
+        // Ingredients without a density are still caught
+        let garlic = s.replace("Serve with butter.", "Add the garlic.");
+        assert_eq!(
+            garlic.parse::<Recipe>().unwrap().validate(),
+            vec![RecipeWarning::UnlistedIngredient {
+                step: 2,
+                ingredient: "garlic".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_nutrition() {
+        let s = "Pasta\n\nserves: 4\n\n---ingredients\n500 g pasta\n\n---nutrition\ncalories: 1800\nprotein: 60g\nsodium: 400 mg\n\n---steps\nBoil the pasta";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let nutrition = recipe.nutrition.unwrap();
+
+        assert_eq!(nutrition.calories, Some(1800.0));
+        assert_eq!(nutrition.protein, Some(60.0));
+        assert_eq!(nutrition.sodium, Some(0.4));
+        assert_eq!(nutrition.fat, None);
+
+        let per_serving = recipe.per_serving().unwrap();
+        assert_eq!(per_serving.calories, Some(450.0));
+        assert_eq!(per_serving.protein, Some(15.0));
+        assert_eq!(
+            per_serving.facts(),
+            vec![
+                ("Calories", 450.0, "kcal"),
+                ("Protein", 15.0, "g"),
+                ("Sodium", 100.0, "mg")
+            ]
+        );
+
+        let scaled = recipe.scale(1.5, Rounding::Exact);
+        assert_eq!(scaled.servings, Some(6));
+        assert_eq!(scaled.per_serving().unwrap().calories, Some(450.0));
+        assert_eq!(scaled.per_serving().unwrap().protein, Some(15.0));
+        assert_eq!(recipe.scale(0.5, Rounding::Exact).servings, Some(2));
+        assert_eq!(recipe.scale(0.1, Rounding::Exact).servings, Some(1));
+
+        let salt = s.replace("sodium: 400 mg", "salt: 1 g");
+        let salt = salt.parse::<Recipe>().unwrap().nutrition.unwrap();
+        assert_eq!(salt.sodium, Some(0.4));
+
+        let no_servings = s.replace("serves: 4\n", "");
+        assert!(no_servings
+            .parse::<Recipe>()
+            .unwrap()
+            .per_serving()
+            .is_none());
+        assert!("Pasta\n\n---ingredients\n500 g pasta\n\n---steps\nBoil"
+            .parse::<Recipe>()
+            .unwrap()
+            .nutrition
+            .is_none());
+    }
+
+    #[test]
+    fn parse_invalid_nutrition() {
+        let s = "Pasta\n\n---ingredients\n500 g pasta\n\n---nutrition\ncalories: lots\n\n---steps\nBoil";
+        let err = s.parse::<Recipe>().unwrap_err();
+
+        assert_eq!(err.line(), Some(7));
+        assert!(matches!(
+            err.inner(),
+            RecipeError::InvalidNutrition { key, value } if key == "calories" && value == "lots"
+        ));
+
+        let unknown = s.replace("calories: lots", "vibes: 10");
+        assert!(matches!(
+            unknown.parse::<Recipe>().unwrap_err().inner(),
+            RecipeError::UnknownNutrient(key) if key == "vibes"
+        ));
+    }
+
+    #[test]
+    fn parse_rice_cup_ingredients() {
+        let rice = "5 rice cups day old rice".parse::<Ingredient>().unwrap();
+        let flour = "200 g rice flour".parse::<Ingredient>().unwrap();
+
+        assert_eq!(rice.ingredient, "day old rice");
+        assert!(matches!(rice.quantity, Some(IngredientQuantity::Volume(_))));
+        assert_eq!(flour.ingredient, "rice flour");
+        assert!(matches!(
+            flour.quantity,
+            Some(IngredientQuantity::Weight(_))
+        ));
+    }
+
+    #[test]
+    fn validate_zero_quantity() {
+        let s = "Tea\n\n---ingredients\n0 g sugar\n250 ml water\n\n---steps\nPour the water, add the sugar.";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(
+            recipe.validate(),
+            vec![RecipeWarning::ZeroQuantity("sugar".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_difficulty_and_rating() {
+        let s = "Soup\n\ndifficulty: Medium\nrating: 4.5\n\n---ingredients\n1 onion\n\n---steps\nCook the onion";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(recipe.difficulty, Some(Difficulty::Medium));
+        assert_eq!(recipe.rating, Some(4.5));
+        assert_eq!(Difficulty::Medium.to_string(), "Medium");
+
+        let none = "Soup\n\n---ingredients\n1 onion\n\n---steps\nCook the onion";
+        let recipe = none.parse::<Recipe>().unwrap();
+        assert_eq!(recipe.difficulty, None);
+        assert_eq!(recipe.rating, None);
+    }
+
+    #[test]
+    fn reject_invalid_difficulty_and_rating() {
+        let s = |header: &str| {
+            format!("Soup\n\n{header}\n\n---ingredients\n1 onion\n\n---steps\nCook")
+                .parse::<Recipe>()
+                .unwrap_err()
+        };
+
+        assert!(matches!(
+            s("rating: 7").inner(),
+            RecipeError::InvalidRating(r) if r == "7"
+        ));
+        assert!(matches!(
+            s("rating: -1").inner(),
+            RecipeError::InvalidRating(_)
+        ));
+        assert!(matches!(
+            s("rating: great").inner(),
+            RecipeError::InvalidRating(_)
+        ));
+        assert!(matches!(
+            s("difficulty: impossible").inner(),
+            RecipeError::InvalidDifficulty(d) if d == "impossible"
+        ));
+        assert_eq!(s("difficulty: impossible").line(), Some(3));
+    }
+
+    #[test]
+    fn pluralize_counts() {
+        let display =
+            |s: &str, factor: f64| s.parse::<Ingredient>().unwrap().scale(factor).to_string();
+
+        assert_eq!(display("2 eggs", 0.5), "1 egg");
+        assert_eq!(display("1 egg", 2.0), "2 eggs");
+        assert_eq!(display("1 banana", 0.5), "0.5 bananas");
+        assert_eq!(display("1 banana", 1.5), "1.5 bananas");
+        assert_eq!(display("1 clove garlic", 3.0), "3 cloves garlic");
+        assert_eq!(display("2 cloves garlic", 0.5), "1 clove garlic");
+        assert_eq!(display("2 bay leaves", 0.5), "1 bay leaf");
+        assert_eq!(display("1 bay leaf", 2.0), "2 bay leaves");
+        assert_eq!(display("1 cherry", 2.0), "2 cherries");
+        assert_eq!(display("1 brown onion", 2.0), "2 brown onions");
+        assert_eq!(display("2-3 eggs", 1.0), "2-3 eggs");
+        assert_eq!(display("2 boxes of pasta", 1.0), "2 boxes of pasta");
+        assert_eq!(display("2 glass of milk", 1.0), "2 glasses of milk");
+        assert_eq!(display("2 bags of rice", 0.5), "1 bag of rice");
+        assert_eq!(display("4 salmon", 1.0), "4 salmon");
+        assert_eq!(display("2 garlic", 1.0), "2 garlic");
+        assert_eq!(display("3 rice", 1.0), "3 rice");
+        assert_eq!(display("200 g eggs", 1.0), "200 g eggs");
+        assert_eq!(display("2-3 cups flour", 1.0), "0.5-0.7 l flour");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn parse_yaml_frontmatter() {
+        let txt = "Toast\n\nimage: toast.png | Two slices of toast\nserves: 2\ntags: Breakfast, quick\ndifficulty: easy\nrating: 4\nprep: 5m\n\nThe **simplest** breakfast.\n\n---ingredients\n2 slices bread\n# Topping\n10 g butter\n\n---steps\nToast the bread\n\nSpread the butter";
+        let yaml = r##"---
+title: Toast
+image: toast.png | Two slices of toast
+servings: 2
+tags: [Breakfast, quick, breakfast]
+difficulty: easy
+rating: 4
+prep: 5m
+ingredients:
+  - 2 slices bread
+  - "# Topping"
+  - 10 g butter
+steps:
+  - Toast the bread
+  - Spread the butter
+---
+
+The **simplest** breakfast.
+"##;
+
+        assert_eq!(
+            Recipe::from_yaml_frontmatter(yaml).unwrap(),
+            txt.parse::<Recipe>().unwrap()
+        );
+
+        assert!(matches!(
+            Recipe::from_yaml_frontmatter("title: Toast"),
+            Err(RecipeError::ExpectedFrontMatter)
+        ));
+        assert!(matches!(
+            Recipe::from_yaml_frontmatter(&yaml.replace("rating: 4", "rating: 9")),
+            Err(RecipeError::InvalidRating(_))
+        ));
+        assert!(matches!(
+            Recipe::from_yaml_frontmatter(&yaml.replace("title: Toast\n", "")),
+            Err(RecipeError::CustomString(_))
+        ));
+    }
+
+    #[test]
+    fn sort_recipes() {
+        let recipe = |title: &str, times: &str, ingredients: &str| {
+            format!("{title}\n\n{times}\n---ingredients\n{ingredients}\n\n---steps\nCook")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+
+        let stew = recipe(
+            "stew",
+            "prep: 20m\ncook: 2h\n",
+            "1 onion\n1 carrot\n1 potato",
+        );
+        let apple = recipe("Apple", "", "1 apple");
+        let toast = recipe("Toast", "cook: 5m\n", "1 bread\n10 g butter");
+        let titles =
+            |recipes: &[&Recipe]| recipes.iter().map(|r| r.title.clone()).collect::<Vec<_>>();
+
+        let mut recipes = vec![&stew, &apple, &toast];
+        RecipeSort::Title.sort(&mut recipes);
+        assert_eq!(titles(&recipes), ["Apple", "stew", "Toast"]);
+
+        RecipeSort::IngredientCount.sort(&mut recipes);
+        assert_eq!(titles(&recipes), ["Apple", "Toast", "stew"]);
+
+        RecipeSort::TotalTime.sort(&mut recipes);
+        assert_eq!(titles(&recipes), ["Toast", "stew", "Apple"]);
+
+        RecipeSort::Relevance.sort(&mut recipes);
+        assert_eq!(titles(&recipes), ["Toast", "stew", "Apple"]);
+        assert_eq!(stew.total_time(), Some(Duration::from_minutes(140)));
+
+        for sort in RecipeSort::ALL {
+            assert_eq!(sort.to_string().parse::<RecipeSort>().unwrap(), sort);
+        }
+    }
+
+    #[test]
+    fn parse_prep_notes() {
+        let onion = "1 onion, finely diced".parse::<Ingredient>().unwrap();
+        let garlic = "2 cloves garlic, minced, or crushed (optional)"
+            .parse::<Ingredient>()
+            .unwrap();
+        let salt = "salt".parse::<Ingredient>().unwrap();
+
+        assert_eq!(onion.ingredient, "onion");
+        assert_eq!(onion.prep.as_deref(), Some("finely diced"));
+        assert_eq!(onion.to_string(), "1 onion, finely diced");
+        assert_eq!(onion.scale(2.0).to_string(), "2 onions, finely diced");
+
+        assert_eq!(garlic.ingredient, "cloves garlic");
+        assert_eq!(garlic.prep.as_deref(), Some("minced, or crushed"));
+        assert_eq!(
+            garlic.to_string(),
+            "2 cloves garlic, minced, or crushed (optional)"
+        );
+
+        assert_eq!(salt.ingredient, "salt");
+        assert_eq!(salt.prep, None);
+        assert_eq!(salt.to_string(), "salt");
+        assert_eq!("salt,".parse::<Ingredient>().unwrap().prep, None);
+    }
+
+    #[test]
+    fn quantity_kinds() {
+        let kind = |s: &str| s.parse::<Ingredient>().unwrap().quantity.unwrap().kind();
+
+        assert_eq!(kind("200 g flour"), Some(QuantityKind::Weight));
+        assert_eq!(kind("1-2 cups milk"), Some(QuantityKind::Volume));
+        assert_eq!(kind("3 eggs"), None);
+
+        let q = "1 1/2 cups milk"
+            .parse::<Ingredient>()
+            .unwrap()
+            .quantity
+            .unwrap();
+        assert_eq!(q.clone().as_imperial_uk().as_metric(), q);
+        assert_eq!(q.scale(2.0).as_imperial().to_string(), "3 cups");
+    }
+
+    #[test]
+    fn parse_sub_recipe_refs() {
+        let step = Step::from("Pour the [[Béchamel sauce]] over the @ragu, then [[serve");
+
+        assert_eq!(
+            step.sub_recipes(),
+            vec![SubRecipeRef {
+                name: "Béchamel sauce".into()
+            }]
+        );
+        assert_eq!(step.sub_recipes()[0].href(), "/recipe/béchamel_sauce");
+        assert_eq!(SubRecipeRef::parse_leading("[[]] x"), None);
+        assert_eq!(SubRecipeRef::parse_leading("@ragu x"), None);
+
+        let ingredient = "200 ml [[béchamel]], warm".parse::<Ingredient>().unwrap();
+
+        assert_eq!(ingredient.ingredient, "béchamel");
+        assert_eq!(ingredient.prep.as_deref(), Some("warm"));
+        assert_eq!(
+            ingredient.sub_recipe,
+            Some(SubRecipeRef {
+                name: "béchamel".into()
+            })
+        );
+        assert_eq!(ingredient.to_string(), "200 ml béchamel, warm");
+        assert_eq!("2 eggs".parse::<Ingredient>().unwrap().sub_recipe, None);
+    }
+
+    #[test]
+    fn resolve_sub_recipe_refs() {
+        let recipe = |s: &str| s.parse::<Recipe>().unwrap();
+        let index = RecipeIndex::new(&[
+            (
+                "egg_fried_rice".into(),
+                recipe("Egg fried rice\n\n---ingredients\n2 eggs\n\n---steps\nFry"),
+            ),
+            (
+                "bechamel".into(),
+                recipe("Béchamel sauce\n\n---ingredients\nmilk\n\n---steps\nStir"),
+            ),
+        ]);
+        let resolve = |name: &str| index.resolve(&SubRecipeRef { name: name.into() });
+
+        assert_eq!(resolve("egg fried rice"), Some("egg_fried_rice"));
+        assert_eq!(resolve("Egg_Fried_Rice"), Some("egg_fried_rice"));
+        assert_eq!(resolve("béchamel sauce"), Some("bechamel"));
+        assert_eq!(resolve("bechamel"), Some("bechamel"));
+        assert_eq!(resolve("ragu"), None);
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn markdown_sub_recipe_links() {
+        assert_eq!(
+            markdown_to_html("Add the [[béchamel]] and @ragu_sauce, not a@b"),
+            "Add the <a href=\"/recipe/béchamel\">béchamel</a> and @ragu_sauce, not a@b"
+        );
+        assert_eq!(
+            markdown_to_html_with("Add the [[Béchamel sauce]]", |_| "/recipe/bechamel".into()),
+            "Add the <a href=\"/recipe/bechamel\">Béchamel sauce</a>"
+        );
+    }
+
+    #[test]
+    fn precise_ingredient_weights() {
+        let yeast = "7.5 g yeast"
+            .parse::<Ingredient>()
+            .unwrap()
+            .quantity
+            .unwrap();
+        let salt = "1.2-1.5 kg salt"
+            .parse::<Ingredient>()
+            .unwrap()
+            .quantity
+            .unwrap();
+
+        assert_eq!(yeast.to_string(), "8 g");
+        assert_eq!(format!("{yeast:#}"), "7.5 g");
+        assert_eq!(format!("{salt:#}"), "1200-1500 g");
+
+        let doubled = "3.75 g yeast".parse::<Ingredient>().unwrap().scale(2.0);
+        assert_eq!(doubled.to_string_in(UnitSystem::Metric), "8 g yeast");
+        assert_eq!(
+            doubled.to_precise_string_in(UnitSystem::Metric),
+            "7.5 g yeast"
+        );
+    }
+
+    fn assert_round_trips(recipe: &Recipe) {
+        let txt = recipe.to_recipe_txt();
+        let mut parsed = txt
+            .parse::<Recipe>()
+            .unwrap_or_else(|e| panic!("{e}\n{txt}"));
+
+        // Quantities without an original one, like scaled ones, are written
+        // fresh and become the original when parsed back
+        let parsed_ingredients = parsed.ingredients.sections.iter_mut();
+        let ingredients = parsed_ingredients.flat_map(|s| s.ingredients.iter_mut());
+
+        for (parsed, ingredient) in ingredients.zip(recipe.ingredients.iter()) {
+            if ingredient.original.is_none() {
+                parsed.original = None;
+            }
+        }
+
+        assert_eq!(&parsed, recipe, "\n{txt}");
+    }
+
+    #[test]
+    fn bundled_recipes_round_trip() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/public/recipes");
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let txt = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let recipe = txt.parse::<Recipe>().unwrap();
+
+            for factor in [1.0, 0.5, 1.5, 2.0 / 3.0, 7.3] {
+                assert_round_trips(&recipe.scale(factor, Rounding::Exact));
+            }
+        }
+    }
+
+    #[test]
+    fn full_recipe_round_trips() {
+        let s = "Lasagne\n\n\
+            image: lasagne.jpg | A slice of lasagne\n\
+            servings: 6\n\
+            tags: dinner, pasta\n\
+            difficulty: hard\n\
+            rating: 4.5\n\
+            prep: 45 minutes\n\
+            cook: 1 hour\n\n\
+            Worth the effort.\n\n\
+            ---ingredients\n\
+            12 lasagne sheets\n\
+            # Sauce\n\
+            500 ml [[béchamel]], warm\n\
+            1 1/2 cups passata\n\
+            1-2 tbsp olive oil (optional)\n\
+            2 to 3 cloves garlic, minced\n\
+            1 lb minced beef\n\
+            1.15 g nutmeg\n\n\
+            ---nutrition\n\
+            calories: 3200\n\
+            sodium: 1200 mg\n\n\
+            ---steps\n\
+            Make the [[ragu]].\n\nLayer and bake at 180 C.";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_round_trips(&recipe);
+        assert_round_trips(&recipe.scale(1.7, Rounding::Exact));
+        let txt = recipe.to_recipe_txt();
+        let doubled = recipe.scale(2.0, Rounding::Exact).to_recipe_txt();
+
+        assert!(txt.contains("\n1 1/2 cups passata\n"));
+        assert!(txt.contains("\n1.15 g nutmeg\n"));
+        assert!(doubled.contains("\n3 cups passata\n"));
+        assert!(doubled.contains("\n2-4 tbsp olive oil (optional)\n"));
+        assert!(doubled.contains("\n2.3 g nutmeg\n"));
+    }
+
+    #[test]
+    fn parse_ingredient_lines() {
+        let ingredients = "200 g flour\n\n# Topping\n  sugar\n"
+            .parse::<Ingredients>()
+            .unwrap();
+
+        assert_eq!(ingredients.sections.len(), 2);
+        assert_eq!(ingredients.sections[1].name, "Topping");
+        assert_eq!(
+            ingredients.to_recipe_txt(),
+            "200 g flour\n# Topping\nsugar\n"
+        );
+    }
+
+    mod parse_recipe {
+        use super::*;
+
+        fn parse_err(s: &str) -> (Option<usize>, RecipeError) {
+            let err = s.parse::<Recipe>().unwrap_err();
+            (err.line(), err.inner().clone())
+        }
+
+        #[test]
+        fn missing_title() {
+            assert_eq!(
+                parse_err("Just a title"),
+                (Some(1), RecipeError::ExpectedTitle)
+            );
+            assert_eq!(parse_err(""), (Some(1), RecipeError::ExpectedTitle));
+        }
+
+        #[test]
+        fn image_without_href() {
+            let with_alt = "Toast\n\nimage: | Toast\n\n---ingredients\nbread\n\n---steps\nToast";
+            let empty = "Toast\n\nimage:\n\n---ingredients\nbread\n\n---steps\nToast";
+
+            assert_eq!(
+                parse_err(with_alt),
+                (Some(3), RecipeError::ExpectedImageHref)
+            );
+            assert_eq!(parse_err(empty), (Some(3), RecipeError::ExpectedImageHref));
+        }
+
+        #[test]
+        fn missing_ingredients() {
+            let s = "Toast\n\nCrunchy.\n\n---steps\nToast the bread";
+
+            assert_eq!(
+                parse_err(s),
+                (Some(5), RecipeError::ExpectedIngredientsStart)
+            );
+        }
+
+        #[test]
+        fn introduction_without_blank_line() {
+            let s = "Toast\n\nCrunchy.\n---ingredients\nbread\n\n---steps\nToast the bread";
+            let recipe = s.parse::<Recipe>().unwrap();
+
+            assert_eq!(recipe.introduction.as_deref(), Some("Crunchy."));
+            assert_eq!(recipe.ingredients.len(), 1);
+
+            let unterminated = "Toast\n\nCrunchy.\n---steps\nToast the bread";
+            let no_sections = "Toast\n\n---steps\nToast the bread";
+
+            assert_eq!(
+                parse_err(unterminated),
+                (Some(3), RecipeError::ExpectedIngredientsStart)
+            );
+            assert_eq!(
+                parse_err(no_sections),
+                (Some(3), RecipeError::ExpectedIngredientsStart)
+            );
+        }
+
+        #[test]
+        fn crlf_line_endings() {
+            let lf = include_str!("../public/recipes/egg_fried_rice.txt").replace("\r\n", "\n");
+            let crlf = lf.replace('\n', "\r\n");
+
+            assert_eq!(
+                crlf.parse::<Recipe>().unwrap(),
+                lf.parse::<Recipe>().unwrap()
+            );
+
+            let missing_steps = "Toast\r\n\r\n---ingredients\r\nbread\r\n\r\nToast it";
+            assert_eq!(
+                parse_err(missing_steps),
+                (Some(6), RecipeError::ExpectedStepsStart)
+            );
+        }
+
+        #[test]
+        fn missing_steps() {
+            let s = "Toast\n\n---ingredients\nbread\n\nToast the bread";
+            let typo = "Toast\n\n---ingredients\nbread\n\n---step\nToast the bread";
+
+            assert_eq!(parse_err(s), (Some(6), RecipeError::ExpectedStepsStart));
+            assert_eq!(parse_err(typo), (Some(6), RecipeError::ExpectedStepsStart));
+        }
+
+        #[test]
+        fn empty_ingredient_line() {
+            let blank = "Toast\n\n---ingredients\nbread\n   \nbutter\n\n---steps\nToast";
+            let only_marker = "Toast\n\n---ingredients\nbread\n(optional)\n\n---steps\nToast";
+
+            // A line of spaces ends the ingredients like any other blank line
+            assert_eq!(parse_err(blank), (Some(6), RecipeError::ExpectedStepsStart));
+            assert_eq!(
+                parse_err(only_marker),
+                (Some(5), RecipeError::ExpectedIngredient)
+            );
+            assert_eq!(
+                "".parse::<Ingredient>(),
+                Err(RecipeError::ExpectedIngredient)
+            );
+        }
+
+        #[test]
+        fn error_lines_count_comments() {
+            let s = "Toast\n// breakfast\n\n---ingredients\n#! from the corner shop\nbread\n(optional)\n\n---steps\nToast";
+
+            assert_eq!(parse_err(s), (Some(7), RecipeError::ExpectedIngredient));
+        }
+
+        #[test]
+        fn eof_in_ingredients() {
+            let no_newline = "Toast\n\n---ingredients\nbread";
+            let no_blank_line = "Toast\n\n---ingredients\nbread\nbutter\n";
+            let eof = RecipeError::UnexpectedEOF("Ingredient".into());
+
+            assert_eq!(parse_err(no_newline), (Some(4), eof.clone()));
+            assert_eq!(parse_err(no_blank_line), (Some(6), eof));
+        }
+
+        #[test]
+        fn all_sections() {
+            let s = "Pancakes\n\n\
+                image: /img/pancakes.jpg | A stack of pancakes\n\
+                serves: 4 people\n\
+                tags: Breakfast, sweet, breakfast\n\
+                difficulty: easy\n\
+                rating: 4\n\
+                prep time: 10 min\n\
+                cook time: 20 min\n\n\
+                Fluffy and *quick*.\n\n\
+                ---ingredients\n\
+                250 g flour\n\
+                2 eggs, beaten\n\
+                # Topping\n\
+                2-3 tbsp maple syrup (optional)\n\n\
+                ---nutrition\n\
+                calories: 1600 kcal\n\
+                protein: 48 g\n\n\
+                ---steps\n\
+                Whisk the flour and eggs.\n\nFry in a pan.\nFlip once.\n";
+            let recipe = s.parse::<Recipe>().unwrap();
+
+            assert_eq!(recipe.title, "Pancakes");
+            assert_eq!(
+                recipe.image,
+                Some(Image {
+                    href: "/img/pancakes.jpg".into(),
+                    alt: Some("A stack of pancakes".into()),
+                })
+            );
+            assert_eq!(recipe.servings, Some(4));
+            assert_eq!(recipe.tags, ["breakfast", "sweet"]);
+            assert_eq!(recipe.difficulty, Some(Difficulty::Easy));
+            assert_eq!(recipe.rating, Some(4.0));
+            assert_eq!(recipe.prep_time, Some(Duration::from_secs(600)));
+            assert_eq!(recipe.cook_time, Some(Duration::from_secs(1200)));
+            assert_eq!(recipe.introduction.as_deref(), Some("Fluffy and *quick*."));
+
+            let sections = &recipe.ingredients.sections;
+            assert_eq!(sections.len(), 2);
+            assert_eq!(sections[0].name, "");
+            assert_eq!(sections[0].ingredients[1].to_string(), "2 eggs, beaten");
+            assert_eq!(sections[1].name, "Topping");
+            assert!(sections[1].ingredients[0].optional);
+            assert_eq!(sections[1].ingredients[0].ingredient, "maple syrup");
+
+            let nutrition = recipe.nutrition.unwrap();
+            assert_eq!(nutrition.calories, Some(1600.0));
+            assert_eq!(nutrition.protein, Some(48.0));
+            assert_eq!(nutrition.fat, None);
+
+            let steps = recipe
+                .steps
+                .iter()
+                .map(|s| s.body.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                steps,
+                ["Whisk the flour and eggs.", "Fry in a pan.\nFlip once."]
+            );
+        }
+    }
+
+    #[test]
+    fn canonical_ingredients() {
+        assert_eq!(canonical_ingredient("Tomatoes"), "tomato");
+        assert_eq!(canonical_ingredient(" Roma  tomatoes "), "tomato");
+        assert_eq!(canonical_ingredient("spring onions"), "spring onion");
+        assert_eq!(canonical_ingredient("scallions"), "spring onion");
+        assert_eq!(canonical_ingredient("cloves garlic"), "clove garlic");
+
+        let recipe = |ingredients: &str| {
+            format!("Salad\n\n---ingredients\n{ingredients}\n\n---steps\nMix")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+        let merged = shopping_list(&[recipe("2 tomatoes"), recipe("1 tomato")]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].to_string(), "3 tomatoes");
+
+        let list = shopping_list(&[
+            recipe("2 tomatoes\nsalt"),
+            recipe("1 tomato\nSalt"),
+            recipe("1 roma tomato"),
+        ]);
+        let list = list.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(list, ["4 tomatoes", "salt"]);
+
+        let index = RecipeIndex::new(&[("salad".into(), recipe("3 Tomatoes"))]);
+
+        assert_eq!(index.by_ingredient("tomato"), ["salad"]);
+        assert_eq!(index.by_ingredient("roma tomatoes"), ["salad"]);
+    }
+
+    #[test]
+    fn pick_random_recipe() {
+        let names = ["boscaiola", "egg_fried_rice", "lasagne"].map(String::from);
+
+        assert_eq!(pick_recipe(&[], 7, None), None);
+        assert_eq!(pick_recipe(&names, 7, None), pick_recipe(&names, 7, None));
+        assert_eq!(
+            pick_recipe(&names[..1], 7, Some("boscaiola")),
+            Some("boscaiola")
+        );
+
+        let mut previous = None;
+        let mut seen = Vec::new();
+
+        for seed in 0..50 {
+            let name = pick_recipe(&names, seed, previous).unwrap();
+
+            assert!(names.iter().any(|n| n == name));
+            assert_ne!(Some(name), previous);
+            previous = Some(name);
+            seen.push(name);
+        }
+
+        seen.sort_unstable();
+        seen.dedup();
+        assert_eq!(seen.len(), names.len());
+    }
+
+    #[test]
+    fn paginate_lists() {
+        let items = (1..=23).collect::<Vec<_>>();
+
+        assert_eq!(paginate(&items, 1, 10), (&items[..10], 3));
+        assert_eq!(paginate(&items, 2, 10), (&items[10..20], 3));
+        assert_eq!(paginate(&items, 3, 10), (&[21, 22, 23][..], 3));
+        assert_eq!(paginate(&items, 4, 10), (&[21, 22, 23][..], 3));
+        assert_eq!(paginate(&items, 0, 10), (&items[..10], 3));
+        assert_eq!(paginate(&items[..20], 2, 10), (&items[10..20], 2));
+        assert_eq!(paginate(&items, 1, 0), (&[1][..], 23));
+        assert_eq!(paginate::<u8>(&[], 1, 10), (&[][..], 1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn schema_org_jsonld() {
+        let s = "Bread\n\nservings: 2\ntags: baking, bread\nprep: 20 min\ncook: 1h 10m\n\n\
+            ---ingredients\n500 g flour\n2 tsp salt\n\n\
+            ---nutrition\ncalories: 1200\nsodium: 800 mg\n\n\
+            ---steps\nKnead.\n\nBake.";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let ld = serde_json::from_str::<serde_json::Value>(&recipe.to_schema_org_jsonld()).unwrap();
+
+        assert_eq!(ld["@context"], "https://schema.org");
+        assert_eq!(ld["@type"], "Recipe");
+        assert_eq!(ld["name"], "Bread");
+        assert_eq!(ld["recipeYield"], "2");
+        assert_eq!(ld["keywords"], "baking, bread");
+        assert_eq!(ld["prepTime"], "PT20M");
+        assert_eq!(ld["cookTime"], "PT1H10M");
+        assert_eq!(ld["totalTime"], "PT1H30M");
+        assert_eq!(ld["recipeIngredient"][0], "500 g flour");
+        assert_eq!(ld["recipeInstructions"][1]["text"], "Bake.");
+        assert_eq!(ld["nutrition"]["sodiumContent"], "800 mg");
+        assert!(ld.get("image").is_none());
+        assert!(ld.get("description").is_none());
+    }
+
+    #[test]
+    fn keep_original_quantity() {
+        let cheese = "8 oz cheese".parse::<Ingredient>().unwrap();
+
+        assert_eq!(cheese.original.as_deref(), Some("8 oz"));
+        assert_eq!(cheese.to_string_in(UnitSystem::Imperial), "8 oz cheese");
+        assert_eq!(cheese.to_string_in(UnitSystem::ImperialUk), "8 oz cheese");
+        assert_eq!(cheese.to_string_in(UnitSystem::Metric), "227 g cheese");
+        assert_eq!(
+            cheese.scale(2.0).to_string_in(UnitSystem::Imperial),
+            "1.0 lb cheese"
+        );
+
+        let milk = "1 1/2 cups milk, warm (optional)"
+            .parse::<Ingredient>()
+            .unwrap();
+
+        assert_eq!(
+            milk.to_string_in(UnitSystem::Imperial),
+            "1 1/2 cups milk, warm (optional)"
+        );
+        assert_eq!(milk.to_recipe_txt(), "1 1/2 cups milk, warm (optional)");
+
+        let eggs = "2 to 3 egg".parse::<Ingredient>().unwrap();
+
+        assert_eq!(eggs.to_string_in(UnitSystem::Metric), "2 to 3 eggs");
+        assert_eq!("salt".parse::<Ingredient>().unwrap().original, None);
+    }
+
+    #[test]
+    fn parse_qualifiers() {
+        let salt = "salt to taste".parse::<Ingredient>().unwrap();
+
+        assert_eq!(salt.ingredient, "salt");
+        assert_eq!(salt.qualifier.as_deref(), Some("to taste"));
+        assert_eq!(salt.quantity, None);
+        assert_eq!(salt.to_string(), "salt, to taste");
+        assert_eq!(salt.scale(3.0), salt);
+
+        let pepper = "pepper, as needed".parse::<Ingredient>().unwrap();
+
+        assert_eq!(pepper.ingredient, "pepper");
+        assert_eq!(pepper.prep, None);
+        assert_eq!(pepper.qualifier.as_deref(), Some("as needed"));
+
+        let parsley = "1 tbsp parsley, chopped, for garnish (optional)"
+            .parse::<Ingredient>()
+            .unwrap();
+
+        assert_eq!(parsley.ingredient, "parsley");
+        assert_eq!(parsley.prep.as_deref(), Some("chopped"));
+        assert_eq!(parsley.qualifier.as_deref(), Some("for garnish"));
+        assert!(parsley.optional);
+        assert_eq!(
+            parsley.to_recipe_txt().parse::<Ingredient>().unwrap(),
+            parsley
+        );
+
+        let potatoes = "2 potatoes, boiled to serve".parse::<Ingredient>().unwrap();
+        assert_eq!(potatoes.prep.as_deref(), Some("boiled"));
+        assert_eq!("pastry".parse::<Ingredient>().unwrap().qualifier, None);
+    }
+
+    #[test]
+    fn rounding_policies() {
+        let s = "Omelette\n\n---ingredients\n3 eggs\n1/4 cup milk\n137 g cheese\n1 onion\n\n---steps\nCook it";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let quantities = |rounding| {
+            recipe
+                .scale(1.5, rounding)
+                .ingredients
+                .iter()
+                .map(|i| i.quantity.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let exact = quantities(Rounding::Exact);
+        assert_eq!(exact[0], IngredientQuantity::Count(4.5));
+        assert_eq!(
+            exact[1],
+            IngredientQuantity::Volume(Volume::from_raw(88_721))
+        );
+
+        let friendly = quantities(Rounding::FriendlyFractions);
+        assert_eq!(friendly[0], IngredientQuantity::Count(5.0));
+        assert_eq!(
+            friendly[1],
+            IngredientQuantity::Volume(Volume::from_raw(Volume::<Metric>::TBSP * 6))
+        );
+        assert_eq!(
+            friendly[2],
+            IngredientQuantity::Weight(Weight::new_metric(205_000))
+        );
+        assert_eq!(friendly[3], IngredientQuantity::Count(1.5));
+
+        let whole = quantities(Rounding::NearestWhole);
+        assert_eq!(whole[0], IngredientQuantity::Count(5.0));
+        assert_eq!(whole[1], friendly[1]);
+        assert_eq!(whole[3], IngredientQuantity::Count(2.0));
+
+        let egg = IngredientQuantity::<Metric>::Count(1.0).scale(0.1);
+        assert_eq!(
+            egg.round(Rounding::NearestWhole, UnitSystem::Metric),
+            IngredientQuantity::Count(1.0)
+        );
+        let volume = |ul| IngredientQuantity::<Metric>::Volume(Volume::from_raw(ul));
+        assert_eq!(
+            volume(Volume::<Metric>::TSP * 3 / 8)
+                .round(Rounding::FriendlyFractions, UnitSystem::Imperial),
+            volume(Volume::<Metric>::DASH * 3)
+        );
+        assert_eq!(
+            volume(562_500).round(Rounding::FriendlyFractions, UnitSystem::Metric),
+            volume(575_000)
+        );
+        assert_eq!(
+            volume(18_480).round(Rounding::FriendlyFractions, UnitSystem::Metric),
+            volume(20_000)
+        );
+
+        let stock = "Soup\n\n---ingredients\n375 ml stock\n\n---steps\nHeat it"
+            .parse::<Recipe>()
+            .unwrap()
+            .scale(1.5, Rounding::FriendlyFractions);
+        assert_eq!(
+            stock.ingredients.iter().next().unwrap().quantity,
+            Some(volume(575_000))
+        );
+    }
+
+    #[test]
+    fn list_summary() {
+        let parse = |header: &str| {
+            format!("Rice\n\n{header}\n\n---ingredients\n1 cup rice\n\n---steps\nCook it")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+
+        assert_eq!(
+            parse("prep: 5m\ncook: 10m\nservings: 2")
+                .list_summary()
+                .as_deref(),
+            Some("15m · serves 2")
+        );
+        assert_eq!(parse("cook: 1h").list_summary().as_deref(), Some("1h"));
+        assert_eq!(
+            parse("servings: 4").list_summary().as_deref(),
+            Some("serves 4")
+        );
+        assert_eq!(parse("Plain boiled rice.").list_summary(), None);
+    }
+
+    #[test]
+    fn base_paths() {
+        let recipe = "recipes/egg_fried_rice.txt";
+
+        assert_eq!(join_path("", recipe), "/recipes/egg_fried_rice.txt");
+        assert_eq!(join_path("/", "/list"), "/list");
+        assert_eq!(
+            join_path("/recipe-book", recipe),
+            "/recipe-book/recipes/egg_fried_rice.txt"
+        );
+        assert_eq!(join_path("recipe-book/", "/list"), "/recipe-book/list");
+        assert_eq!(recipe_file_url("", "egg_fried_rice"), join_path("", recipe));
+        assert_eq!(
+            recipe_file_url("/recipe-book/", "egg_fried_rice"),
+            "/recipe-book/recipes/egg_fried_rice.txt"
+        );
+
+        let link = recipe_route("stew");
+        assert_eq!(
+            leptos_router::resolve_path("/recipe-book", &link, Some("/recipe-book/list")).unwrap(),
+            "/recipe-book/recipe/stew"
+        );
+        assert_eq!(
+            leptos_router::resolve_path("", &link, Some("/list")).unwrap(),
+            join_path("", &link)
+        );
+        assert_eq!(
+            join_path("/recipe-book/", &link),
+            "/recipe-book/recipe/stew"
+        );
+
+        assert_eq!(strip_base("", "/recipe/stew"), "/recipe/stew");
+        assert_eq!(
+            strip_base("/recipe-book", "/recipe-book/recipe/stew"),
+            "/recipe/stew"
+        );
+        assert_eq!(strip_base("/recipe-book/", "/recipe-book"), "");
+        assert_eq!(
+            strip_base("/recipe", "/recipes/stew.txt"),
+            "/recipes/stew.txt"
+        );
+    }
+
+    #[test]
+    fn reading_time() {
+        let recipe = |intro_words: usize, step_words: usize| {
+            let intro = "word ".repeat(intro_words);
+            let steps = "word ".repeat(step_words);
+
+            format!("Soup\n\n{intro}\n\n---ingredients\n1 onion\n\n---steps\n{steps}")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+
+        assert_eq!(recipe(0, 0).word_count(), 0);
+        assert_eq!(recipe(0, 0).reading_time(), Duration::from_minutes(0));
+        assert_eq!(recipe(20, 30).word_count(), 50);
+        assert_eq!(recipe(20, 30).reading_time(), Duration::from_minutes(1));
+        assert_eq!(recipe(100, 300).reading_time(), Duration::from_minutes(2));
+        assert_eq!(recipe(0, 401).reading_time(), Duration::from_minutes(3));
+        assert_eq!(
+            recipe(0, 20_000).reading_time(),
+            Duration::from_minutes(100)
+        );
+    }
+
+    #[test]
+    fn ingredient_mentions_in_steps() {
+        let ingredients = ["Garlic", "sauce", "soy sauce", "eggs", "spring onion"];
+        let step = "Fry the garlic, then add the Soy Sauce and an egg. Top with spring onions.";
+        let mentioned = ingredient_mentions(step, &ingredients)
+            .into_iter()
+            .map(|(range, index)| (&step[range], index))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            mentioned,
+            [
+                ("garlic", 0),
+                ("Soy Sauce", 2),
+                ("egg", 3),
+                ("spring onions", 4)
+            ]
+        );
+
+        assert!(ingredient_mentions("Stir the soy, sauce", &["soy sauce"]).is_empty());
+        assert!(ingredient_mentions("Add the garlicky oil", &["garlic"]).is_empty());
+        assert!(ingredient_mentions("Serve", &[""]).is_empty());
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn link_ingredients_in_html() {
+        let html = markdown_to_html("Whisk the **eggs** into [more eggs](/eggs) with [[rice]]");
+
+        assert_eq!(
+            link_ingredients(&html, &["egg", "rice"], "mention"),
+            "Whisk the <strong><a class=\"mention\" href=\"#ingredient-0\">eggs</a></strong> \
+             into <a href=\"/eggs\">more eggs</a> with <a href=\"/recipe/rice\">rice</a>"
+        );
+    }
+
+    #[test]
+    fn ingredients_summary() {
+        let s = "Pancakes\n\n---ingredients\n200 g flour\n2-3 eggs\nsalt to taste\n\n---steps\nMix";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(
+            recipe.ingredients_summary(UnitSystem::Metric),
+            "200 g flour, 2-3 eggs, salt, to taste"
+        );
+        assert_eq!(
+            recipe.ingredients_summary(UnitSystem::Imperial),
+            "7.1 oz flour, 2-3 eggs, salt, to taste"
+        );
+
+        let s = "Pie\n\n---ingredients\n# Crust\n250 g flour\n# Filling\n4 apples\n1 tsp cinnamon\n\n---steps\nBake";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(
+            recipe.ingredients_summary(UnitSystem::Metric),
+            "Crust: 250 g flour; Filling: 4 apples, 1 tsp cinnamon"
+        );
+    }
+
+    #[test]
+    fn consolidated_ingredients() {
+        let s = "Stew\n\n---ingredients\n# Stew\n1 tsp salt\n2 onions\n500 g beef\n\
+                 # Dumplings\n1/2 tsp salt\n1 onion\n1 cup flour\n100 g flour\n\n---steps\nCook";
+        let recipe = s.parse::<Recipe>().unwrap();
+        let consolidated = recipe.consolidated_ingredients();
+        let salt = "1 1/2 tsp salt".parse::<Ingredient>().unwrap();
+
+        assert_eq!(consolidated[0].quantity, salt.quantity);
+        assert_eq!(
+            consolidated
+                .iter()
+                .map(Ingredient::to_recipe_txt)
+                .collect::<Vec<_>>(),
+            [
+                "1.5 tsp salt",
+                "3 onions",
+                "500 g beef",
+                "1 cup flour",
+                "100 g flour"
+            ]
+        );
+    }
+
+    #[test]
+    fn share_options() {
+        let shared = ShareOptions::from_query("?unit=imperial&scale=2");
+
+        assert_eq!(shared.unit, Some(UnitSystem::Imperial));
+        assert_eq!(shared.scale, 2.0);
+        assert_eq!(shared.to_query(), "unit=imperial&scale=2");
+        assert_eq!(ShareOptions::from_query(&shared.to_query()), shared);
+
+        let uk = ShareOptions {
+            unit: Some(UnitSystem::ImperialUk),
+            scale: 0.5,
+        };
+        assert_eq!(ShareOptions::from_query(&uk.to_query()), uk);
+
+        assert_eq!(ShareOptions::from_query(""), ShareOptions::default());
+        assert_eq!(ShareOptions::default().to_query(), "");
+        assert_eq!(
+            ShareOptions::from_query("unit=furlongs&scale=-1&other=1"),
+            ShareOptions::default()
+        );
+        assert_eq!(ShareOptions::from_query("scale=NaN").scale, 1.0);
+        assert_eq!(ShareOptions::from_query("scale=inf").scale, 1.0);
+        assert_eq!(ShareOptions::from_query("scale=1.5&unit").scale, 1.5);
+    }
+
+    #[test]
+    fn quantity_try_into_measurement() {
+        let quantity = |s: &str| s.parse::<Ingredient>().unwrap().quantity.unwrap();
+
+        assert_eq!(
+            Weight::try_from(quantity("200 g flour")),
+            Ok("200 g".parse().unwrap())
+        );
+        assert_eq!(
+            Volume::try_from(quantity("1 cup milk")),
+            Ok("1 cup".parse().unwrap())
+        );
+
+        assert_eq!(
+            Weight::try_from(quantity("1 cup milk")),
+            Err(quantity("1 cup milk"))
+        );
+        assert_eq!(
+            Volume::try_from(quantity("2 eggs")),
+            Err(quantity("2 eggs"))
+        );
+        assert!(Weight::try_from(quantity("1-2 kg potatoes")).is_err());
+    }
+
+    #[test]
+    fn parse_alternatives() {
+        let butter = "2 tbsp butter or olive oil, melted"
+            .parse::<Ingredient>()
+            .unwrap();
+
+        assert_eq!(butter.ingredient, "butter");
+        assert_eq!(butter.prep.as_deref(), Some("melted"));
+        assert_eq!(
+            butter.quantity,
+            Some(IngredientQuantity::Volume("2 tbsp".parse().unwrap()))
+        );
+        assert_eq!(butter.alternatives.len(), 1);
+        assert_eq!(butter.alternatives[0].ingredient, "olive oil");
+        assert_eq!(butter.alternatives[0].quantity, None);
+        assert_eq!(
+            butter.to_string_in(UnitSystem::Imperial),
+            "2 tbsp butter (or olive oil), melted"
+        );
+        assert_eq!(butter.to_recipe_txt(), "2 tbsp butter or olive oil, melted");
+
+        let fat = "1 tbsp butter or 1 tsp oil or lard"
+            .parse::<Ingredient>()
+            .unwrap();
+
+        assert_eq!(fat.ingredient, "butter");
+        assert_eq!(
+            fat.alternatives[0].quantity,
+            Some(IngredientQuantity::Volume("1 tsp".parse().unwrap()))
+        );
+        assert_eq!(fat.alternatives[1].ingredient, "lard");
+        assert_eq!(
+            fat.clone().as_imperial().to_string(),
+            "1 tbsp butter (or 1 tsp oil or lard)"
+        );
+        assert_eq!(
+            fat.scale(0.5).as_imperial().to_string(),
+            "1/2 tbsp butter (or 1/2 tsp oil or lard)"
+        );
+
+        let orange = "1 orange".parse::<Ingredient>().unwrap();
+        assert!(orange.alternatives.is_empty());
+    }
+
+    #[test]
+    fn scale_no_scale_sections() {
+        let recipe = "Pizza\n\n\
+            ---ingredients\n\n\
+            # Dough\n\
+            500 g flour\n\
+            2 tsp salt\n\
+            # Garnish (no-scale)\n\
+            1 tbsp olive oil\n\
+            5 basil leaves\n\n\
+            ---steps\n\n\
+            Make the dough with the flour and salt, garnish with olive oil and basil.\n"
+            .parse::<Recipe>()
+            .unwrap();
+
+        let garnish = &recipe.ingredients.sections[1];
+        assert_eq!(garnish.name, "Garnish");
+        assert!(garnish.no_scale);
+        assert!(!recipe.ingredients.sections[0].no_scale);
+
+        let doubled = recipe.scale(2.0, Rounding::FriendlyFractions);
+        let names = |r: &Recipe| {
+            r.ingredients
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(&doubled),
+            [
+                "1.0 kg flour",
+                "20 ml salt",
+                "15 ml olive oil",
+                "5 basil leaves"
+            ]
+        );
+        assert_eq!(doubled.ingredients.sections[1], *garnish);
+
+        let txt = recipe.to_recipe_txt();
+        assert!(txt.contains("# Garnish (no-scale)\n"));
+        assert_eq!(txt.parse::<Recipe>().unwrap(), recipe);
+
+        // Scaled first, the garnish still only counts once per recipe
+        let list = shopping_list(&[doubled.clone(), doubled]);
+        assert_eq!(list[2].to_string(), "30 ml olive oil");
+        assert_eq!(list[3].to_string(), "10 basil leaves");
+    }
+
+    #[test]
+    fn servings_scale() {
+        let recipe =
+            "Soup\n\nservings: 4\n\n---ingredients\n\n1 l stock\n\n---steps\n\nHeat the stock.\n"
+                .parse::<Recipe>()
+                .unwrap();
+
+        assert_eq!(recipe.servings_scale(4), 1.0);
+        assert_eq!(recipe.servings_scale(6), 1.5);
+        assert_eq!(recipe.servings_scale(1), 0.25);
+        // Clamped to one serving
+        assert_eq!(recipe.servings_scale(0), 0.25);
+
+        let no_servings = Recipe {
+            servings: None,
+            ..recipe.clone()
+        };
+        assert_eq!(no_servings.servings_scale(3), 3.0);
+
+        let zero_servings = Recipe {
+            servings: Some(0),
+            ..recipe
+        };
+        assert_eq!(zero_servings.servings_scale(2), 2.0);
+    }
+
+    #[test]
+    fn estimated_cost() {
+        let recipe = "Pancakes\n\n\
+            servings: 4\n\n\
+            ---ingredients\n\n\
+            250 g flour\n\
+            500 ml milk\n\
+            2 eggs\n\
+            salt, to taste\n\n\
+            ---steps\n\n\
+            Whisk the flour, milk, eggs and salt.\n"
+            .parse::<Recipe>()
+            .unwrap();
+
+        let mut prices = PriceTable::new();
+        prices.insert("flour", Price::PerKg(Money(120)));
+        prices.insert("milk", Price::PerLiter(Money(90)));
+        // Measured differently from how it's priced
+        prices.insert("salt", Price::PerKg(Money(50)));
+
+        let estimate = recipe.estimated_cost(&prices);
+
+        assert_eq!(estimate.total, Money(75));
+        assert_eq!(estimate.total.to_string(), "0.75");
+        assert_eq!(estimate.unpriced, ["eggs", "salt"]);
+        assert_eq!(estimate.total / 4, Money(19));
+
+        prices.insert("egg", Price::PerItem(Money(35)));
+        assert_eq!(recipe.estimated_cost(&prices).total, Money(145));
+
+        assert_eq!(
+            recipe.estimated_cost(&PriceTable::new()),
+            CostEstimate {
+                total: Money(0),
+                unpriced: vec!["flour".into(), "milk".into(), "eggs".into(), "salt".into()],
+            }
+        );
+
+        let doubled = recipe.scale(2.0, Rounding::Exact);
+        assert_eq!(doubled.servings, Some(8));
+        assert_eq!(
+            doubled.estimated_cost(&prices).total / 8,
+            recipe.estimated_cost(&prices).total / 4
+        );
+    }
+
+    #[test]
+    fn parse_decimal_comma_ingredient() {
+        let flour = "1,5 kg flour, sifted".parse::<Ingredient>().unwrap();
+
+        assert_eq!(flour.ingredient, "flour");
+        assert_eq!(flour.prep.as_deref(), Some("sifted"));
+        assert_eq!(
+            flour.quantity,
+            Some(IngredientQuantity::Weight("1.5 kg".parse().unwrap()))
+        );
+        assert_eq!(
+            DecimalStyle::Comma.display(&flour).to_string(),
+            "1,5 kg flour, sifted"
+        );
+
+        let water = "1,000 ml water".parse::<Ingredient>().unwrap();
+        assert_eq!(
+            water.quantity,
+            Some(IngredientQuantity::Volume("1 l".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn parse_substeps() {
+        let recipe = "Salad\n\n---ingredients\n\n1 lettuce\n1 lemon\n\n\
+            ---steps\n\n\
+            Make the dressing:\n  zest the lemon\n  squeeze in the juice for 2 minutes\n\n\
+            Toss the lettuce.\n- dress it\nServe."
+            .parse::<Recipe>()
+            .unwrap();
+
+        assert_eq!(recipe.steps.len(), 2);
+        assert_eq!(recipe.steps[0].body, "Make the dressing:");
+        assert_eq!(
+            recipe.steps[0].substeps,
+            ["zest the lemon", "squeeze in the juice for 2 minutes"]
+        );
+        assert_eq!(recipe.steps[0].durations(), vec![Duration::from_minutes(2)]);
+        assert_eq!(recipe.steps[1].body, "Toss the lettuce.\nServe.");
+        assert_eq!(recipe.steps[1].substeps, ["dress it"]);
+        assert!(recipe.validate().is_empty());
+
+        let txt = recipe.to_recipe_txt();
+        assert!(txt.contains("Make the dressing:\n- zest the lemon\n- squeeze"));
+        assert_eq!(txt.parse::<Recipe>().unwrap(), recipe);
+
+        assert_eq!(Step::from("Bake it").substeps, Vec::<String>::new());
+    }
+
+    #[test]
+    fn blank_lines_before_steps() {
+        let recipe = |gap: &str| {
+            format!("Toast\n\n---ingredients\n\n2 slices bread\n{gap}---steps\n\nToast the bread.")
+                .parse::<Recipe>()
+        };
+        let expected = recipe("\n").unwrap();
+
+        assert_eq!(recipe("\n\n").unwrap(), expected);
+        assert_eq!(recipe("   \n").unwrap(), expected);
+        assert_eq!(recipe(" \t\n  \n\n").unwrap(), expected);
+        assert_eq!(recipe("").unwrap(), expected);
+        assert_eq!(expected.ingredients.len(), 1);
+    }
+
+    #[test]
+    fn allergens() {
+        let found = |s: &str| Allergen::in_ingredient(&s.parse::<Ingredient>().unwrap().ingredient);
+
+        assert_eq!(found("200 g butter"), [Allergen::Dairy]);
+        assert_eq!(found("3 eggs"), [Allergen::Eggs]);
+        assert_eq!(found("2 tbsp peanut butter"), [Allergen::Nuts]);
+        assert_eq!(found("400 ml coconut milk"), []);
+        assert_eq!(found("1 cup almond milk"), [Allergen::Nuts]);
+        assert_eq!(found("100 g rice flour"), []);
+        assert_eq!(found("1 aubergine"), []);
+        assert_eq!(found("1 tsp nutmeg"), []);
+        assert_eq!(found("water"), []);
+
+        let recipe = "Pancakes\n\n\
+            ---ingredients\n\n\
+            250 g flour\n\
+            500 ml milk\n\
+            2 eggs\n\
+            1 tbsp butter or oil\n\n\
+            ---steps\n\n\
+            Whisk the flour, milk and eggs.\n"
+            .parse::<Recipe>()
+            .unwrap();
+
+        assert_eq!(
+            recipe.allergens(),
+            HashSet::from([Allergen::Gluten, Allergen::Dairy, Allergen::Eggs])
+        );
+    }
+
+    #[test]
+    fn incremental_index() {
+        let sources = vec![
+            (
+                "egg_fried_rice",
+                include_str!("../public/recipes/egg_fried_rice.txt"),
+            ),
+            ("broken", "Not a recipe"),
+            ("boscaiola", include_str!("../public/recipes/boscaiola.txt")),
+        ];
+        let mut incremental = IncrementalIndex::new(sources.clone());
+
+        assert_eq!(incremental.progress(), (0, 3));
+        assert!(incremental.index().by_tag("dinner").is_empty());
+
+        assert!(!incremental.index_chunk(2));
+        assert_eq!(incremental.progress(), (2, 3));
+        assert_eq!(incremental.recipes().len(), 1);
+        assert_eq!(incremental.failures().len(), 1);
+        assert_eq!(incremental.failures()[0].0, "broken");
+        assert_eq!(incremental.index().by_tag("dinner"), ["egg_fried_rice"]);
+        assert_eq!(
+            incremental.index().by_ingredient("egg"),
+            vec!["egg_fried_rice"]
+        );
+        assert!(incremental.index().by_tag("pasta").is_empty());
+
+        let (recipes, index) = incremental.finish();
+        assert_eq!(index, RecipeIndex::new(&recipes));
+        assert_eq!(index.by_tag("dinner"), ["egg_fried_rice", "boscaiola"]);
+        assert_eq!(index.by_tag("Pasta"), ["boscaiola"]);
+        assert_eq!(recipes.len(), 2);
+
+        let mut chunked = IncrementalIndex::new(sources);
+        while !chunked.index_chunk(1) {}
+        assert_eq!(chunked.index(), &index);
+    }
+
+    #[test]
+    fn comments() {
+        let s = "Toast\n\n\
+            // TODO: try it with sourdough\n\
+            ---ingredients\n\
+            # Toast\n\
+            #! two slices is plenty\n\
+            2 slices bread\n\
+            // 1 tbsp butter\n\
+            \n\
+            ---steps\n\
+            Toast the bread.\n\
+            \n\
+            // Butter it?\n\
+            \n\
+            Serve.\n\
+            \x20   #! while it's warm\n";
+        let recipe = s.parse::<Recipe>().unwrap();
+
+        assert_eq!(recipe.ingredients.sections.len(), 1);
+        assert_eq!(recipe.ingredients.sections[0].name, "Toast");
+        assert_eq!(recipe.ingredients.len(), 1);
+        assert_eq!(
+            recipe.ingredients.sections[0].ingredients[0].ingredient,
+            "slices bread"
+        );
+        assert_eq!(
+            recipe.steps,
+            [Step::from("Toast the bread."), Step::from("Serve.")]
+        );
+        assert!(!recipe.to_recipe_txt().contains("//"));
+
+        assert!(is_comment("  // indented"));
+        assert!(!is_comment("# Sauce"));
+        assert!(!is_comment("1 tsp salt // to taste"));
+    }
+
+    #[test]
+    fn diff_recipes() {
+        let recipe = |ingredients: &str, steps: &str| {
+            format!("Toast\n\n---ingredients\n{ingredients}\n\n---steps\n{steps}")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+        let ingredient = |s: &str| s.parse::<Ingredient>().unwrap();
+
+        let before = recipe(
+            "2 slices bread\n1 tbsp butter",
+            "Toast the bread.\n\nButter it.\n\nServe.",
+        );
+        assert!(before.diff(&before).is_empty());
+
+        let after = recipe(
+            "2 slices bread\n2 tbsp butter\n1 tbsp jam",
+            "Toast the bread.\n\nServe.",
+        );
+        let diff = before.diff(&after);
+
+        assert_eq!(
+            diff.ingredients,
+            [
+                Change::Changed {
+                    before: ingredient("1 tbsp butter"),
+                    after: ingredient("2 tbsp butter"),
+                },
+                Change::Added(ingredient("1 tbsp jam")),
+            ]
+        );
+        assert_eq!(diff.steps, [Change::Removed(Step::from("Butter it."))]);
+
+        let reworded = recipe(
+            "2 slices bread\n1 tbsp butter",
+            "Toast the bread.\n\nSpread the butter on it.\n\nServe.",
+        );
+        assert_eq!(
+            before.diff(&reworded).steps,
+            [Change::Changed {
+                before: Step::from("Butter it."),
+                after: Step::from("Spread the butter on it."),
+            }]
+        );
+        assert_eq!(
+            after.diff(&before).ingredients[1],
+            Change::Removed(ingredient("1 tbsp jam"))
+        );
+    }
+
+    #[test]
+    fn build_recipe() {
+        let built = RecipeBuilder::new("Pancakes")
+            .servings(4)
+            .tag("Breakfast")
+            .ingredient(Ingredient::new("flour").weight_g(200.0))
+            .ingredient(Ingredient::new("milk").volume_ml(250.0))
+            .ingredient(Ingredient::new("eggs").count(2.0))
+            .section("Topping")
+            .ingredient(Ingredient::new("butter").weight_g(12.5).prep("melted"))
+            .ingredient(Ingredient::new("salt"))
+            .step("Whisk the flour, milk and eggs.")
+            .step("Fry in the butter.\n- Flip once")
+            .build();
+
+        let parsed = "Pancakes\n\n\
+            servings: 4\n\
+            tags: breakfast\n\n\
+            ---ingredients\n\
+            200 g flour\n\
+            250 ml milk\n\
+            2 eggs\n\
+            # Topping\n\
+            12.5 g butter, melted\n\
+            salt\n\n\
+            ---steps\n\
+            Whisk the flour, milk and eggs.\n\n\
+            Fry in the butter.\n\
+            - Flip once\n"
+            .parse::<Recipe>()
+            .unwrap();
+
+        assert_eq!(built, parsed);
+        assert_eq!(built.to_recipe_txt().parse::<Recipe>().unwrap(), built);
+    }
+
+    #[test]
+    fn forced_unit() {
+        let flour = "2 cups flour, sifted [imperial]"
+            .parse::<Ingredient>()
+            .unwrap();
+
+        assert_eq!(flour.forced_unit, Some(UnitSystem::Imperial));
+        assert_eq!(flour.prep.as_deref(), Some("sifted"));
+        assert_eq!(
+            flour.to_string_in(UnitSystem::Metric),
+            "2 cups flour, sifted"
+        );
+        assert_eq!(
+            flour.to_string_in(UnitSystem::Imperial),
+            "2 cups flour, sifted"
+        );
+        assert_eq!(flour.to_recipe_txt(), "2 cups flour, sifted [imperial]");
+
+        let spray = "1 ml cooking spray (optional) [metric]"
+            .parse::<Ingredient>()
+            .unwrap();
+        assert!(spray.optional);
+        assert_eq!(
+            spray.to_string_in(UnitSystem::Imperial),
+            "1 ml cooking spray (optional)"
+        );
+
+        let built = Ingredient::new("flour")
+            .volume_ml(120.0)
+            .forced_unit(UnitSystem::Imperial);
+        assert_eq!(built.to_string_in(UnitSystem::Metric), "1/2 cup flour");
+
+        // Sub-recipe links and unknown annotations stay part of the name
+        let sauce = "200 ml [[béchamel]]".parse::<Ingredient>().unwrap();
+        assert_eq!(sauce.forced_unit, None);
+        assert_eq!(sauce.ingredient, "béchamel");
+        assert_eq!(
+            "1 tsp salt [fine]"
+                .parse::<Ingredient>()
+                .unwrap()
+                .ingredient,
+            "salt [fine]"
+        );
+    }
+
+    #[test]
+    fn shopping_list_checklist() {
+        let bread = "Bread\n\n---ingredients\n200 g flour\n2 eggs\nsalt\n\n---steps\nBake"
+            .parse::<Recipe>()
+            .unwrap();
+        let cake = "Cake\n\n---ingredients\n200 g flour\n1 egg\n100 g butter\n\n---steps\nBake"
+            .parse::<Recipe>()
+            .unwrap();
+        let recipes = [bread, cake];
+
+        assert_eq!(
+            shopping_list_text(&recipes, UnitSystem::Metric, None),
+            "Shopping list for Bread, Cake\n\
+            \n\
+            [ ] 400 g flour\n\
+            [ ] 3 eggs\n\
+            [ ] salt\n\
+            [ ] 100 g butter\n"
+        );
+
+        let mut aisles = AisleTable::new();
+        aisles.insert("flour", "Baking");
+        aisles.insert("Eggs", "Dairy");
+        aisles.insert("butter", "Dairy");
+
+        assert_eq!(
+            shopping_list_text(&recipes, UnitSystem::Metric, Some(&aisles)),
+            "Shopping list for Bread, Cake\n\
+            \n\
+            Baking\n\
+            [ ] 400 g flour\n\
+            \n\
+            Dairy\n\
+            [ ] 3 eggs\n\
+            [ ] 100 g butter\n\
+            \n\
+            Other\n\
+            [ ] salt\n"
+        );
+        assert!(
+            shopping_list_text(&recipes, UnitSystem::Imperial, None).contains("[ ] 0.9 lb flour\n")
+        );
+    }
+
+    #[test]
+    fn empty_sections() {
+        let drink = "Lemonade\n\n---ingredients\n\n1 l water\n2 lemons\n\n---steps\n"
+            .parse::<Recipe>()
+            .unwrap();
+
+        assert!(drink.steps.is_empty());
+        assert_eq!(drink.validate(), [RecipeWarning::NoSteps]);
+        assert_eq!(drink.to_recipe_txt().parse::<Recipe>().unwrap(), drink);
+
+        let toast = "Toast\n\n---ingredients\n\n---steps\n\nToast some bread."
+            .parse::<Recipe>()
+            .unwrap();
+
+        assert!(toast.ingredients.is_empty());
+        assert!(toast.ingredients.sections.is_empty());
+        assert_eq!(toast.steps, [Step::from("Toast some bread.")]);
+        assert_eq!(toast.validate(), [RecipeWarning::NoIngredients]);
+        assert_eq!(toast.to_recipe_txt().parse::<Recipe>().unwrap(), toast);
+
+        let nothing = "Nothing\n\n---ingredients\n---steps"
+            .parse::<Recipe>()
+            .unwrap();
+        assert_eq!(
+            nothing.validate(),
+            [RecipeWarning::NoIngredients, RecipeWarning::NoSteps]
+        );
+    }
+
+    #[test]
+    fn dominant_unit_system() {
+        let recipe = |ingredients: &str| {
+            format!("Cookies\n\n---ingredients\n{ingredients}\n\n---steps\nBake")
+                .parse::<Recipe>()
+                .unwrap()
+        };
+
+        let imperial = recipe("2 cups flour\n8 oz butter\n1 tsp salt\n2 eggs\n100 g sugar");
+        assert_eq!(imperial.dominant_unit_system(), Some(UnitSystem::Imperial));
+        // Scaling drops the quantities as written
+        assert_eq!(
+            imperial.scale(2.0, Rounding::Exact).dominant_unit_system(),
+            None
+        );
+
+        let metric = recipe("250 g flour\n1 l milk\n1 cup sugar");
+        assert_eq!(metric.dominant_unit_system(), Some(UnitSystem::Metric));
+
+        assert_eq!(recipe("1 tsp salt\n2 eggs").dominant_unit_system(), None);
+        assert_eq!(
+            recipe("1 lb flour\n500 ml milk").dominant_unit_system(),
+            None
+        );
+    }
+
+    #[test]
+    fn temperatures_in_steps() {
+        let imperial = |s: &str| convert_temperatures(s, UnitSystem::Imperial);
+        let metric = |s: &str| convert_temperatures(s, UnitSystem::Metric);
+
+        assert_eq!(imperial("Preheat to 200C."), "Preheat to 392°F.");
+        assert_eq!(imperial("Preheat to 200 C."), "Preheat to 392°F.");
+        assert_eq!(
+            imperial("Preheat to 200°C, then bake"),
+            "Preheat to 392°F, then bake"
+        );
+        assert_eq!(
+            convert_temperatures("Bake at 180 °c", UnitSystem::Imperial),
+            "Bake at 356°F"
+        );
+        assert_eq!(
+            convert_temperatures("Bake at 180 °c or 350F", UnitSystem::ImperialUk),
+            "Bake at 180 °c or 177°C"
+        );
+        assert_eq!(
+            imperial("Heat the oil to 170-180C"),
+            "Heat the oil to 338-356°F"
+        );
+        assert_eq!(
+            metric("Bake at 350F for 20 minutes"),
+            "Bake at 177°C for 20 minutes"
+        );
+        assert_eq!(metric("Bake at 350–375 °F"), "Bake at 177–191°C");
+
+        // Already in the right scale
+        assert_eq!(metric("Preheat to 200C"), "Preheat to 200C");
+        assert_eq!(imperial("Preheat to 400 F"), "Preheat to 400 F");
+
+        // Already in both scales
+        for s in [
+            "Bake at 200C/400F",
+            "Bake at 400°F/200°C.",
+            "Heat to 170-180C/340-355F",
+        ] {
+            assert_eq!(imperial(s), s);
+            assert_eq!(metric(s), s);
+        }
+        assert_eq!(imperial("Bake at 200C/220C"), "Bake at 392°F/428°F");
+
+        // Not temperatures
+        for s in [
+            "Add 2 C flour",
+            "Serves 4 Cooks",
+            "Cut into 200 Cubes",
+            "Use 2 Fuji apples",
+            "Step 12C",
+            "A1C",
+            "Fold in 3 eggs. Cool",
+        ] {
+            assert_eq!(imperial(s), s);
+        }
+        assert_eq!(imperial("Chill to 4°C"), "Chill to 39°F");
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn load_embedded_recipe() {
+        let recipe = load_recipe_embedded("egg_fried_rice").unwrap();
+        let txt = include_str!("../public/recipes/egg_fried_rice.txt");
+
+        assert_eq!(recipe, txt.parse().unwrap());
+        assert_eq!(
+            load_recipe_embedded("no_such_recipe"),
+            Err(RecipeError::NotFound("no_such_recipe".into()))
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn load_recipe_from_disk() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/public/recipes");
+
+        assert_eq!(
+            load_recipe_file(format!("{dir}/egg_fried_rice.txt")),
+            load_recipe_embedded("egg_fried_rice")
+        );
+        assert!(matches!(
+            load_recipe_file(format!("{dir}/no_such_recipe.txt")),
+            Err(RecipeError::NotFound(_))
+        ));
+    }
 }
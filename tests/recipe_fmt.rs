@@ -0,0 +1,65 @@
+use std::process::{Command, Output};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn recipe_fmt(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_recipe-fmt"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn fixture(name: &str) -> String {
+    format!("{FIXTURES}/{name}")
+}
+
+#[test]
+fn valid_recipe() {
+    let output = recipe_fmt(&[&fixture("toast.txt")]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn warnings() {
+    let output = recipe_fmt(&[&fixture("unused_butter.txt")]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr.contains("warning: `butter` isn't mentioned in any step"));
+}
+
+#[test]
+fn parse_error_outranks_warnings() {
+    let output = recipe_fmt(&[&fixture("unused_butter.txt"), &fixture("no_steps.txt")]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("no_steps.txt: Line 6"));
+    assert_eq!(recipe_fmt(&[]).status.code(), Some(1));
+}
+
+#[test]
+fn write_canonical_form() {
+    let path = std::env::temp_dir().join("recipe_fmt_toast.txt");
+    let path = path.to_str().unwrap();
+    std::fs::copy(fixture("toast.txt"), path).unwrap();
+
+    assert_eq!(recipe_fmt(&["--write", path]).status.code(), Some(0));
+    let formatted = std::fs::read_to_string(path).unwrap();
+
+    assert_eq!(recipe_fmt(&["--write", path]).stdout, b"");
+    assert_eq!(std::fs::read_to_string(path).unwrap(), formatted);
+    assert!(formatted.contains("2 slices bread\n10 g butter"));
+}
+
+#[test]
+fn parsing_prints_nothing() {
+    let recipe = concat!(env!("CARGO_MANIFEST_DIR"), "/public/recipes/egg_fried_rice.txt");
+    let output = recipe_fmt(&[recipe]);
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+}